@@ -5,20 +5,18 @@
 use std::ffi::OsString;
 use std::rc::Rc;
 
-use nixel::ast::AST;
-use nixel::deps::santiago::grammar::Grammar;
-use nixel::deps::santiago::lexer::LexerRules;
-use nixel::grammar::grammar;
-use nixel::lexer::lexer_rules;
-
 use crate::cli::cli_action::CliAction;
 use crate::cli::parse::parse;
-use crate::interpreter::build_ast::build_ast;
 use crate::interpreter::error::Error;
+use crate::interpreter::location::Location;
 use crate::interpreter::runtime::Runtime;
 use crate::interpreter::scope::Scope;
 use crate::interpreter::value::Value;
 
+/// Enough for the maximum depth of evaluation of the interpreter,
+/// even in debug builds, whose frames are larger.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
 pub fn main<I, T>(cli_args: I) -> i32
 where
     I: IntoIterator<Item = T>,
@@ -30,35 +28,50 @@ where
     log::debug!("{:#?}", cli_args);
 
     match cli_args.action {
-        CliAction::Eval { entrypoint } => match main_eval(entrypoint) {
-            Ok(value) => {
-                log::info!("value = {:#?}", &value);
-                0
-            }
-            Err(error) => {
-                log::error!("{}", error);
-                1
+        CliAction::Eval { entrypoint } => {
+            // Evaluation recurses on the native stack,
+            // so give it room for as deep as it may go.
+            let evaluation = std::thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn(move || match main_eval(entrypoint) {
+                    Ok(value) => {
+                        log::info!("value = {}", value);
+                        0
+                    }
+                    Err(error) => {
+                        log::error!("{}", error);
+                        1
+                    }
+                });
+
+            match evaluation {
+                Ok(evaluation) => evaluation
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+                Err(error) => {
+                    log::error!(
+                        "starting the evaluation is not possible: {}",
+                        error
+                    );
+                    1
+                }
             }
-        },
+        }
     }
 }
 
-fn main_eval(entrypoint: String) -> Result<Value, Error> {
-    let lexer_rules: LexerRules = lexer_rules();
-    let grammar: Grammar<AST> = grammar();
+fn main_eval(entrypoint: String) -> Result<Rc<Value>, Error> {
+    let mut runtime = Runtime::new();
     let entrypoint: Rc<String> = Rc::new(entrypoint);
 
-    let ast = build_ast(&lexer_rules, &grammar, &entrypoint)?;
+    let ast = runtime.build_ast(&entrypoint)?;
     log::debug!("ast = {:#?}", &ast);
 
-    let mut runtime = Runtime::new();
     let scope = Scope::empty();
 
-    let value = Rc::new(Value::from_ast(entrypoint, ast, &scope));
-
-    let value = runtime.advance_monotonically(value)?;
+    let value = Rc::new(Value::from_ast(entrypoint.clone(), ast, &scope)?);
 
-    let value = Rc::try_unwrap(value).unwrap();
+    let value = runtime.advance_deeply(value, &Location::InFile(entrypoint))?;
 
     Ok(value)
 }
@@ -36,7 +36,6 @@ where
                 ),
         )
         .after_help(
-            #[cfg_attr(rustfmt, rustfmt_skip)]
             "\
             ---\n\
             By:      Kevin Amado <https://patreon.com/kamadorueda>\n\
@@ -0,0 +1,31 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+/// Nix's base-32 alphabet, which omits `e`, `o`, `u` and `t`.
+const ALPHABET: &[u8; 32] = b"0123456789abcdfghijklmnpqrsvwxyz";
+
+/// Encode `bytes` the way Nix does in store paths and hashes.
+///
+/// Unlike RFC 4648, Nix starts from the last 5-bit group.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let length = (bytes.len() * 8 - 1) / 5 + 1;
+    let mut encoded = String::with_capacity(length);
+
+    for n in (0..length).rev() {
+        let b = n * 5;
+        let i = b / 8;
+        let j = b % 8;
+
+        let c = (bytes[i] as u16 >> j)
+            | if i + 1 < bytes.len() {
+                (bytes[i + 1] as u16) << (8 - j)
+            } else {
+                0
+            };
+
+        encoded.push(ALPHABET[(c & 0x1f) as usize] as char);
+    }
+
+    encoded
+}
@@ -0,0 +1,9 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+pub(crate) mod base32;
+pub(crate) mod md5;
+pub(crate) mod sha1;
+pub(crate) mod sha256;
+pub(crate) mod sha512;
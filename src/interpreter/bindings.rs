@@ -4,64 +4,562 @@
 
 use std::collections::HashMap;
 use std::collections::LinkedList;
+use std::rc::Rc;
 
 use nixel::ast::Attribute;
 use nixel::ast::AttributePath;
 use nixel::ast::Binding as NixelBinding;
+use nixel::ast::StringPart;
 use nixel::ast::AST;
+use nixel::deps::santiago::lexer::Position;
+
+use crate::interpreter::error::Error;
+use crate::interpreter::location::Location;
+use crate::interpreter::location::UNKNOWN_POSITION;
 
 pub(crate) struct Bindings {
-    pub(crate) bindings: HashMap<String, Binding>,
+    pub(crate) bindings:     HashMap<String, Binding>,
+    /// Bindings like `${name} = value;`,
+    /// whose names are known only once evaluated.
+    pub(crate) dynamic:      Vec<(AST, Binding)>,
+    /// The `from` of each `inherit (from) ...;`
+    /// by the name its attributes are selected from,
+    /// so that it is evaluated once for all of them.
+    pub(crate) inherit_from: Vec<(String, AST)>,
 }
 
 #[derive(Debug)]
 pub(crate) struct Binding {
     pub(crate) ast:       AST,
+    /// Whether the binding comes from an `inherit`,
+    /// in which case it is evaluated in the outer scope.
     pub(crate) inherited: bool,
+    pub(crate) position:  Position,
+}
+
+#[derive(Debug)]
+pub(crate) enum AttributeName {
+    /// Known without evaluating anything, like `a` or `"a"`.
+    Static(String),
+    /// Known once evaluated, like `${a}` or `"a${b}"`.
+    Dynamic(AST),
+}
+
+/// The definitions of an attribute set while they are being collected.
+#[derive(Default)]
+struct Definitions {
+    attributes:   HashMap<String, Definition>,
+    dynamic:      Vec<(AST, Definition)>,
+    inherit_from: Vec<(String, AST)>,
+}
+
+/// A binding while the bindings are being collected.
+///
+/// Attribute sets coming from `a.b = ...;` paths
+/// or from literals like `a = { b = ...; };`
+/// are kept open so that later definitions can add attributes to them.
+enum Definition {
+    AttributeSet {
+        definitions: Definitions,
+        /// Where the name of the attribute is
+        defined_at:  Position,
+        /// Whether it is a literal like `a = { b = ...; };`,
+        /// whose attributes cannot be defined again by another literal.
+        literal:     bool,
+        /// Whether it is a literal like `a = rec { b = ...; };`
+        recursive:   bool,
+        /// Where the attribute set starts
+        position:    Position,
+    },
+    /// Comes from an `inherit`,
+    /// or from an `inherit (from)` whose source is bound to `from`.
+    Inherited {
+        from:    Option<String>,
+        binding: Binding,
+    },
+    Value(Binding),
 }
 
 impl Bindings {
-    pub(crate) fn new(bindings: LinkedList<NixelBinding>) -> Bindings {
-        let mut value_bindings = HashMap::new();
+    pub(crate) fn new(
+        path: &Rc<String>,
+        bindings: LinkedList<NixelBinding>,
+    ) -> Result<Bindings, Error> {
+        let definitions = collect_definitions(path, "", bindings)?;
+
+        Ok(Bindings {
+            bindings:     definitions
+                .attributes
+                .into_iter()
+                .map(|(attribute, definition)| {
+                    (attribute, definition.into_binding())
+                })
+                .collect(),
+            dynamic:      definitions
+                .dynamic
+                .into_iter()
+                .map(|(name, definition)| (name, definition.into_binding()))
+                .collect(),
+            inherit_from: definitions.inherit_from,
+        })
+    }
+}
 
-        for binding in bindings {
-            add_binding(&mut value_bindings, binding);
+impl Definition {
+    fn defined_at(&self) -> &Position {
+        match self {
+            Definition::AttributeSet { defined_at, .. } => defined_at,
+            Definition::Inherited { binding, .. }
+            | Definition::Value(binding) => &binding.position,
         }
+    }
+
+    fn into_binding(self) -> Binding {
+        match self {
+            Definition::AttributeSet {
+                definitions,
+                defined_at,
+                recursive,
+                position,
+                ..
+            } => {
+                let mut inherited = LinkedList::new();
+                let mut inherited_from: HashMap<String, LinkedList<Attribute>> =
+                    HashMap::new();
+                let mut bindings = LinkedList::new();
+
+                for (attribute, definition) in definitions.attributes {
+                    match definition {
+                        // Inherits are written again as inherits,
+                        // so that they keep being evaluated in the outer scope
+                        // when the attribute set is recursive
+                        Definition::Inherited { from, binding } => {
+                            let attribute = Attribute::Raw {
+                                content:  attribute,
+                                position: binding.position,
+                            };
 
-        Bindings { bindings: value_bindings }
+                            match from {
+                                None => inherited.push_back(attribute),
+                                Some(from) => inherited_from
+                                    .entry(from)
+                                    .or_default()
+                                    .push_back(attribute),
+                            }
+                        }
+                        definition => {
+                            let binding = definition.into_binding();
+
+                            bindings.push_back(NixelBinding::KeyValue(
+                                AttributePath {
+                                    attributes: LinkedList::from([
+                                        Attribute::Raw {
+                                            content:  attribute,
+                                            position: binding.position,
+                                        },
+                                    ]),
+                                },
+                                Box::new(binding.ast),
+                            ));
+                        }
+                    }
+                }
+
+                for (name, definition) in definitions.dynamic {
+                    bindings.push_back(NixelBinding::KeyValue(
+                        AttributePath {
+                            attributes: LinkedList::from([
+                                Attribute::Expression {
+                                    expression: Box::new(name),
+                                },
+                            ]),
+                        },
+                        Box::new(definition.into_binding().ast),
+                    ));
+                }
+
+                if !inherited.is_empty() {
+                    bindings.push_back(NixelBinding::Inherit(None, inherited));
+                }
+
+                for (name, from) in definitions.inherit_from {
+                    bindings.push_back(NixelBinding::Inherit(
+                        Some(Box::new(from)),
+                        inherited_from.remove(&name).unwrap_or_default(),
+                    ));
+                }
+
+                Binding {
+                    ast:       AST::Map { bindings, recursive, position },
+                    inherited: false,
+                    position:  defined_at,
+                }
+            }
+            Definition::Inherited { binding, .. } => binding,
+            Definition::Value(binding) => binding,
+        }
     }
 }
 
-fn add_binding(bindings: &mut HashMap<String, Binding>, binding: NixelBinding) {
+fn collect_definitions(
+    path: &Rc<String>,
+    prefix: &str,
+    bindings: LinkedList<NixelBinding>,
+) -> Result<Definitions, Error> {
+    let mut definitions = Definitions::default();
+
+    for binding in bindings {
+        add_binding(path, prefix, &mut definitions, binding)?;
+    }
+
+    Ok(definitions)
+}
+
+fn add_binding(
+    path: &Rc<String>,
+    prefix: &str,
+    definitions: &mut Definitions,
+    binding: NixelBinding,
+) -> Result<(), Error> {
     match binding {
         NixelBinding::KeyValue(mut attribute_path, ast) => {
             let attribute = attribute_path.attributes.pop_front().unwrap();
 
-            match attribute {
-                Attribute::Raw { content, .. } => {
-                    bindings.insert(
+            match attribute_name(attribute, &ast.position()) {
+                (AttributeName::Static(content), position) => {
+                    let qualified_name = qualify(prefix, &content);
+                    let definition = definition(
+                        path,
+                        &qualified_name,
+                        position,
+                        attribute_path,
+                        *ast,
+                    )?;
+
+                    merge_definition(
+                        path,
+                        &qualified_name,
+                        &mut definitions.attributes,
                         content,
-                        Binding {
-                            ast:       if attribute_path.attributes.is_empty() {
-                                *ast
-                            } else {
-                                AST::__Bindings(LinkedList::from([
-                                    NixelBinding::KeyValue(
-                                        AttributePath {
-                                            attributes: attribute_path
-                                                .attributes,
-                                        },
-                                        ast,
-                                    ),
-                                ]))
-                            },
-                            inherited: false,
+                        definition,
+                    )
+                }
+                (AttributeName::Dynamic(name), position) => {
+                    let definition = definition(
+                        path,
+                        &qualify(prefix, "${...}"),
+                        position,
+                        attribute_path,
+                        *ast,
+                    )?;
+
+                    definitions.dynamic.push((name, definition));
+
+                    Ok(())
+                }
+            }
+        }
+        NixelBinding::Inherit(from, attributes) => {
+            let from = from.map(|from| {
+                let name = inherit_from_name(&from.position());
+
+                definitions.inherit_from.push((name.clone(), *from.clone()));
+
+                (name, from.position())
+            });
+
+            // Quoted names do not carry a position
+            let fallback = match &from {
+                Some((_, position)) => position.clone(),
+                None => attributes
+                    .iter()
+                    .find_map(|attribute| match attribute {
+                        Attribute::Raw { position, .. } => {
+                            Some(position.clone())
+                        }
+                        Attribute::Expression { .. } => None,
+                    })
+                    .unwrap_or(UNKNOWN_POSITION),
+            };
+
+            for attribute in attributes {
+                let (content, position) =
+                    match attribute_name(attribute, &fallback) {
+                        (AttributeName::Static(content), position) => {
+                            (content, position)
+                        }
+                        (AttributeName::Dynamic(_), position) => {
+                            return Err(Error::Interpreter {
+                                description: "dynamic attributes are not \
+                                              allowed in inherit"
+                                    .to_string(),
+                                location:    Location::at(
+                                    path.clone(),
+                                    &position,
+                                ),
+                                stack:       LinkedList::new(),
+                            });
+                        }
+                    };
+
+                // `inherit a;` is `a = a;` and `inherit (from) a;`
+                // is `a = from.a;`, both in the outer scope
+                let ast = match &from {
+                    None => AST::Variable {
+                        identifier: content.clone(),
+                        position:   position.clone(),
+                    },
+                    Some((name, from_position)) => AST::PropertyAccess {
+                        expression:     Box::new(AST::Variable {
+                            identifier: name.clone(),
+                            position:   from_position.clone(),
+                        }),
+                        attribute_path: AttributePath {
+                            attributes: LinkedList::from([Attribute::Raw {
+                                content:  content.clone(),
+                                position: position.clone(),
+                            }]),
                         },
-                    );
+                        default:        None,
+                    },
+                };
+
+                merge_definition(
+                    path,
+                    &qualify(prefix, &content),
+                    &mut definitions.attributes,
+                    content,
+                    Definition::Inherited {
+                        from:    from.as_ref().map(|(name, _)| name.clone()),
+                        binding: Binding { ast, inherited: true, position },
+                    },
+                )?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// The definition of `qualified_name`,
+/// followed by the rest of the attribute path.
+///
+/// `a.b.c = value;` is the same as `a = { b = { c = value; }; };`
+fn definition(
+    path: &Rc<String>,
+    qualified_name: &str,
+    defined_at: Position,
+    mut attribute_path: AttributePath,
+    ast: AST,
+) -> Result<Definition, Error> {
+    match attribute_path.attributes.pop_front() {
+        None => match ast {
+            AST::Map { bindings, recursive, position } => {
+                Ok(Definition::AttributeSet {
+                    definitions: collect_definitions(
+                        path,
+                        qualified_name,
+                        bindings,
+                    )?,
+                    defined_at,
+                    literal: true,
+                    recursive,
+                    position,
+                })
+            }
+            ast => Ok(Definition::Value(Binding {
+                ast,
+                inherited: false,
+                position: defined_at,
+            })),
+        },
+        Some(attribute) => match attribute_name(attribute, &ast.position()) {
+            (AttributeName::Static(content), position) => {
+                let nested_qualified_name = qualify(qualified_name, &content);
+                let nested_definition = definition(
+                    path,
+                    &nested_qualified_name,
+                    position.clone(),
+                    attribute_path,
+                    ast,
+                )?;
+
+                Ok(Definition::AttributeSet {
+                    definitions: Definitions {
+                        attributes:   HashMap::from([(
+                            content,
+                            nested_definition,
+                        )]),
+                        dynamic:      Vec::new(),
+                        inherit_from: Vec::new(),
+                    },
+                    defined_at,
+                    literal: false,
+                    recursive: false,
+                    position,
+                })
+            }
+            (AttributeName::Dynamic(name), position) => {
+                let nested_definition = definition(
+                    path,
+                    &qualify(qualified_name, "${...}"),
+                    position.clone(),
+                    attribute_path,
+                    ast,
+                )?;
+
+                Ok(Definition::AttributeSet {
+                    definitions: Definitions {
+                        attributes:   HashMap::new(),
+                        dynamic:      vec![(name, nested_definition)],
+                        inherit_from: Vec::new(),
+                    },
+                    defined_at,
+                    literal: false,
+                    recursive: false,
+                    position,
+                })
+            }
+        },
+    }
+}
+
+fn merge_definition(
+    path: &Rc<String>,
+    qualified_name: &str,
+    definitions: &mut HashMap<String, Definition>,
+    attribute: String,
+    definition: Definition,
+) -> Result<(), Error> {
+    match (definitions.get_mut(&attribute), definition) {
+        (None, definition) => {
+            definitions.insert(attribute, definition);
+
+            Ok(())
+        }
+        (
+            Some(Definition::AttributeSet { definitions: existing, .. }),
+            Definition::AttributeSet { definitions: new, literal, .. },
+        ) => {
+            for (attribute, definition) in new.attributes {
+                let qualified_name = qualify(qualified_name, &attribute);
+
+                // Like in Nix, a literal merges one level deep only,
+                // `a = { b = { c = 1; }; }; a = { b = { d = 2; }; };`
+                // defines `a.b` twice
+                match existing.attributes.get(&attribute) {
+                    Some(existing) if literal => {
+                        return Err(already_defined(
+                            path,
+                            &qualified_name,
+                            existing,
+                            &definition,
+                        ));
+                    }
+                    _ => merge_definition(
+                        path,
+                        &qualified_name,
+                        &mut existing.attributes,
+                        attribute,
+                        definition,
+                    )?,
                 }
-                _ => todo!(),
             }
+            existing.dynamic.extend(new.dynamic);
+            existing.inherit_from.extend(new.inherit_from);
+
+            Ok(())
         }
-        _ => todo!(),
+        (Some(existing), definition) => {
+            Err(already_defined(path, qualified_name, existing, &definition))
+        }
+    }
+}
+
+fn already_defined(
+    path: &Rc<String>,
+    qualified_name: &str,
+    existing: &Definition,
+    definition: &Definition,
+) -> Error {
+    let existing = existing.defined_at();
+    let position = definition.defined_at();
+
+    Error::Interpreter {
+        description: match Location::at(path.clone(), existing) {
+            Location::InFile(_) => {
+                format!("attribute {qualified_name:?} already defined")
+            }
+            Location::InFileFragment(_) => format!(
+                "attribute {qualified_name:?} already defined at {}:{}:{}",
+                path, existing.line, existing.column,
+            ),
+        },
+        location:    Location::at(path.clone(), position),
+        stack:       LinkedList::new(),
+    }
+}
+
+/// The name of an attribute and where it is.
+///
+/// The parser does not keep the position of quoted names like `"a"`,
+/// so they are reported at `fallback`.
+pub(crate) fn attribute_name(
+    attribute: Attribute,
+    fallback: &Position,
+) -> (AttributeName, Position) {
+    match attribute {
+        Attribute::Raw { content, position } => {
+            (AttributeName::Static(content), position)
+        }
+        Attribute::Expression { expression } => match *expression {
+            AST::__StringParts(parts) => string_name(parts, fallback.clone()),
+            AST::String { parts, position } => string_name(parts, position),
+            expression => {
+                let position = expression.position();
+
+                (AttributeName::Dynamic(expression), position)
+            }
+        },
+    }
+}
+
+fn string_name(
+    parts: LinkedList<StringPart>,
+    position: Position,
+) -> (AttributeName, Position) {
+    if parts.iter().all(|part| matches!(part, StringPart::Raw { .. })) {
+        let name = parts
+            .into_iter()
+            .map(|part| match part {
+                StringPart::Raw { content } => content,
+                StringPart::Expression { .. } => unreachable!(),
+            })
+            .collect();
+
+        (AttributeName::Static(name), position)
+    } else {
+        (
+            AttributeName::Dynamic(AST::String {
+                parts,
+                position: position.clone(),
+            }),
+            position,
+        )
+    }
+}
+
+/// The name of the `from` of an `inherit (from) ...;`.
+///
+/// It has spaces so that no expression can refer to it.
+fn inherit_from_name(position: &Position) -> String {
+    format!("built-in inherit from {}:{}", position.line, position.column)
+}
+
+fn qualify(prefix: &str, attribute: &str) -> String {
+    if prefix.is_empty() {
+        attribute.to_string()
+    } else {
+        format!("{prefix}.{attribute}")
     }
 }
@@ -2,9 +2,12 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-only
 
+use std::rc::Rc;
+
 use nixel::ast::AST;
 use nixel::deps::santiago::grammar::Grammar;
 use nixel::deps::santiago::lexer::lex;
+use nixel::deps::santiago::lexer::Lexeme;
 use nixel::deps::santiago::lexer::LexerRules;
 use nixel::deps::santiago::parser::parse;
 
@@ -19,6 +22,8 @@ pub(crate) fn build_ast(
     let input = std::fs::read_to_string(path)?;
     log::trace!("lex");
     let lexemes = lex(lexer_rules, &input)?;
+    log::trace!("strip_indentation");
+    let lexemes = strip_indentation(&input, lexemes);
     log::trace!("parse");
     let parse_trees = parse(grammar, &lexemes)?;
     log::trace!("as_abstract_syntax_tree");
@@ -26,3 +31,244 @@ pub(crate) fn build_ast(
 
     Ok(ast)
 }
+
+enum IndentedStringPart {
+    Raw {
+        content:         String,
+        has_indentation: bool,
+        lexeme:          Rc<Lexeme>,
+    },
+    Interpolation(Vec<Rc<Lexeme>>),
+}
+
+/// Rewrite every indented string (`'' ... ''`) into a regular string
+/// (`" ... "`) whose content has the indentation stripped like Nix does.
+///
+/// The parser strips indented strings on its own,
+/// but it disagrees with Nix when lines start with an interpolation,
+/// and when the string does not end in a new line.
+fn strip_indentation(input: &str, lexemes: Vec<Rc<Lexeme>>) -> Vec<Rc<Lexeme>> {
+    let lines: Vec<&str> = input.split('\n').collect();
+
+    match strip_indentation_in(&lines, &lexemes) {
+        Some(lexemes) => lexemes,
+        // Unbalanced input, let the parser report it
+        None => lexemes,
+    }
+}
+
+fn strip_indentation_in(
+    lines: &[&str],
+    lexemes: &[Rc<Lexeme>],
+) -> Option<Vec<Rc<Lexeme>>> {
+    let mut stripped = Vec::with_capacity(lexemes.len());
+    let mut index = 0;
+
+    while index < lexemes.len() {
+        if lexemes[index].kind == "IND_STRING_OPEN" {
+            index =
+                strip_indented_string(lines, lexemes, index, &mut stripped)?;
+        } else {
+            stripped.push(lexemes[index].clone());
+            index += 1;
+        }
+    }
+
+    Some(stripped)
+}
+
+fn strip_indented_string(
+    lines: &[&str],
+    lexemes: &[Rc<Lexeme>],
+    mut index: usize,
+    stripped: &mut Vec<Rc<Lexeme>>,
+) -> Option<usize> {
+    let open = lexemes[index].clone();
+    index += 1;
+
+    let mut parts = Vec::new();
+    let close = loop {
+        let lexeme = lexemes.get(index)?;
+
+        match lexeme.kind.as_str() {
+            "IND_STR" => {
+                parts.push(IndentedStringPart::Raw {
+                    content:         lexeme.raw.clone(),
+                    has_indentation: !is_escaped(lines, lexeme),
+                    lexeme:          lexeme.clone(),
+                });
+                index += 1;
+            }
+            "DOLLAR_CURLY" => {
+                let start = index;
+                let mut depth: usize = 0;
+
+                loop {
+                    match lexemes.get(index)?.kind.as_str() {
+                        "{" | "DOLLAR_CURLY" => depth += 1,
+                        "}" => depth -= 1,
+                        _ => {}
+                    }
+                    index += 1;
+
+                    if depth == 0 {
+                        break;
+                    }
+                }
+
+                parts.push(IndentedStringPart::Interpolation(
+                    strip_indentation_in(lines, &lexemes[start..index])?,
+                ));
+            }
+            "IND_STRING_CLOSE" => {
+                index += 1;
+                break lexeme.clone();
+            }
+            _ => return None,
+        }
+    };
+
+    // Figure out the minimum indentation,
+    // interpolations and escaped characters end the leading whitespace
+    let mut at_start_of_line = true;
+    let mut min_indent = usize::MAX;
+    let mut cur_indent = 0;
+    for part in &parts {
+        match part {
+            IndentedStringPart::Raw {
+                content, has_indentation: true, ..
+            } => {
+                for char in content.chars() {
+                    if at_start_of_line {
+                        match char {
+                            ' ' => cur_indent += 1,
+                            // Empty lines do not count
+                            '\n' => cur_indent = 0,
+                            _ => {
+                                at_start_of_line = false;
+                                min_indent = min_indent.min(cur_indent);
+                            }
+                        }
+                    } else if char == '\n' {
+                        at_start_of_line = true;
+                        cur_indent = 0;
+                    }
+                }
+            }
+            _ => {
+                if at_start_of_line {
+                    at_start_of_line = false;
+                    min_indent = min_indent.min(cur_indent);
+                }
+            }
+        }
+    }
+
+    // Strip the spaces from each line
+    stripped.push(Rc::new(Lexeme {
+        kind:     "\"".to_string(),
+        raw:      "\"".to_string(),
+        position: open.position.clone(),
+    }));
+
+    let mut at_start_of_line = true;
+    let mut cur_dropped = 0;
+    let mut raw: Option<(String, Rc<Lexeme>)> = None;
+    let parts_count = parts.len();
+    for (part_index, part) in parts.into_iter().enumerate() {
+        match part {
+            IndentedStringPart::Raw { content, lexeme, .. } => {
+                let mut content_stripped = String::new();
+
+                for char in content.chars() {
+                    if at_start_of_line {
+                        match char {
+                            ' ' => {
+                                cur_dropped += 1;
+                                if cur_dropped > min_indent {
+                                    content_stripped.push(char);
+                                }
+                            }
+                            '\n' => {
+                                cur_dropped = 0;
+                                content_stripped.push(char);
+                            }
+                            _ => {
+                                at_start_of_line = false;
+                                cur_dropped = 0;
+                                content_stripped.push(char);
+                            }
+                        }
+                    } else {
+                        content_stripped.push(char);
+                        if char == '\n' {
+                            at_start_of_line = true;
+                        }
+                    }
+                }
+
+                // Remove the last line if it consists only of spaces
+                if part_index + 1 == parts_count {
+                    if let Some(last_new_line) = content_stripped.rfind('\n') {
+                        if content_stripped[last_new_line + 1..]
+                            .chars()
+                            .all(|char| char == ' ')
+                        {
+                            content_stripped.truncate(last_new_line + 1);
+                        }
+                    }
+                }
+
+                match &mut raw {
+                    Some((content, _)) => content.push_str(&content_stripped),
+                    None => raw = Some((content_stripped, lexeme)),
+                }
+            }
+            IndentedStringPart::Interpolation(lexemes) => {
+                at_start_of_line = false;
+                cur_dropped = 0;
+
+                push_raw(stripped, raw.take());
+                stripped.extend(lexemes);
+            }
+        }
+    }
+    push_raw(stripped, raw.take());
+
+    stripped.push(Rc::new(Lexeme {
+        kind:     "\"".to_string(),
+        raw:      "\"".to_string(),
+        position: close.position.clone(),
+    }));
+
+    Some(index)
+}
+
+fn push_raw(stripped: &mut Vec<Rc<Lexeme>>, raw: Option<(String, Rc<Lexeme>)>) {
+    if let Some((content, lexeme)) = raw {
+        if !content.is_empty() {
+            stripped.push(Rc::new(Lexeme {
+                kind:     "STR".to_string(),
+                raw:      content,
+                position: lexeme.position.clone(),
+            }));
+        }
+    }
+}
+
+/// Whether the lexeme comes from an escape sequence like `''$` or `''\n`,
+/// in which case its content is never indentation.
+fn is_escaped(lines: &[&str], lexeme: &Lexeme) -> bool {
+    match lexeme.raw.as_str() {
+        "$" | "'" | "''" => true,
+        _ => lines
+            .get(lexeme.position.line - 1)
+            .map(|line| {
+                line.chars()
+                    .skip(lexeme.position.column - 1)
+                    .take(2)
+                    .eq("''".chars())
+            })
+            .unwrap_or(false),
+    }
+}
@@ -13,6 +13,19 @@ use super::runtime_stack_frame::RuntimeStackFrame;
 
 #[derive(Debug)]
 pub(crate) enum Error {
+    /// Raised by `abort`, `tryEval` does not catch it.
+    Abort {
+        description: String,
+        location:    Location,
+        stack:       LinkedList<RuntimeStackFrame>,
+    },
+    /// Raised by an `assert` whose condition is false,
+    /// `tryEval` catches it.
+    Assertion {
+        description: String,
+        location:    Location,
+        stack:       LinkedList<RuntimeStackFrame>,
+    },
     Interpreter {
         description: String,
         location:    Location,
@@ -21,6 +34,12 @@ pub(crate) enum Error {
     IO(std::io::Error),
     Lexer(LexerError),
     Parser(ParseError<AST>),
+    /// Raised by `throw`, `tryEval` catches it.
+    Throw {
+        description: String,
+        location:    Location,
+        stack:       LinkedList<RuntimeStackFrame>,
+    },
 }
 
 impl std::convert::From<std::io::Error> for Error {
@@ -47,7 +66,10 @@ impl std::fmt::Display for Error {
         f: &mut std::fmt::Formatter<'_>,
     ) -> Result<(), std::fmt::Error> {
         match &self {
-            Error::Interpreter { description, location, stack } => {
+            Error::Abort { description, location, stack }
+            | Error::Assertion { description, location, stack }
+            | Error::Interpreter { description, location, stack }
+            | Error::Throw { description, location, stack } => {
                 writeln!(f, "{} error, most recent action last:", self.kind())?;
                 writeln!(f)?;
 
@@ -64,7 +86,13 @@ impl std::fmt::Display for Error {
 
                 Ok(())
             }
-            error => {
+            Error::IO(error) => {
+                writeln!(f, "{} error: {:?}", self.kind(), error)
+            }
+            Error::Lexer(error) => {
+                writeln!(f, "{} error: {:?}", self.kind(), error)
+            }
+            Error::Parser(error) => {
                 writeln!(f, "{} error: {:?}", self.kind(), error)
             }
         }
@@ -72,12 +100,20 @@ impl std::fmt::Display for Error {
 }
 
 impl Error {
+    /// Whether `tryEval` recovers from the error.
+    pub(crate) fn is_catchable(&self) -> bool {
+        matches!(self, Error::Assertion { .. } | Error::Throw { .. })
+    }
+
     fn kind(&self) -> &str {
         match &self {
+            Error::Abort { .. } => "Abort",
+            Error::Assertion { .. } => "Assertion",
             Error::Interpreter { .. } => "Interpreter",
             Error::IO(_) => "Input/Output",
             Error::Lexer(_) => "Lexer",
             Error::Parser(_) => "Parser",
+            Error::Throw { .. } => "Throw",
         }
     }
 }
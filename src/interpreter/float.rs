@@ -0,0 +1,67 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+/// A floating point number that prints like Nix does,
+/// which is C's `%g`: 6 significant digits without trailing zeros,
+/// and scientific notation for very large or very small numbers.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub(crate) struct Float(pub(crate) f64);
+
+impl std::fmt::Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = self.0;
+
+        if value.is_nan() {
+            return write!(
+                f,
+                "{}",
+                if value.is_sign_negative() { "-nan" } else { "nan" }
+            );
+        }
+        if value.is_infinite() {
+            return write!(f, "{}", if value < 0.0 { "-inf" } else { "inf" });
+        }
+        if value == 0.0 {
+            return write!(
+                f,
+                "{}",
+                if value.is_sign_negative() { "-0" } else { "0" }
+            );
+        }
+
+        // The exponent after rounding to 6 significant digits
+        let scientific = format!("{value:.5e}");
+        let (mantissa, exponent) = scientific.split_once('e').unwrap();
+        let exponent: i32 = exponent.parse().unwrap();
+
+        if !(-4..6).contains(&exponent) {
+            let sign = if exponent < 0 { '-' } else { '+' };
+
+            write!(
+                f,
+                "{}e{sign}{:02}",
+                strip_trailing_zeros(mantissa),
+                exponent.abs()
+            )
+        } else {
+            let fixed = format!("{value:.*}", (5 - exponent) as usize);
+
+            write!(f, "{}", strip_trailing_zeros(&fixed))
+        }
+    }
+}
+
+impl std::fmt::Debug for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+fn strip_trailing_zeros(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    }
+}
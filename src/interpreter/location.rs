@@ -5,8 +5,16 @@
 use std::io::Read;
 use std::rc::Rc;
 
+use nixel::deps::santiago::lexer::Position;
+
+/// Where the parser does not say where something is,
+/// like for quoted attribute names.
+pub(crate) const UNKNOWN_POSITION: Position = Position { line: 0, column: 0 };
+
 #[derive(Clone, Debug)]
 pub(crate) enum Location {
+    /// Somewhere in a file, but it is unknown where.
+    InFile(Rc<String>),
     InFileFragment(LocationInFileFragment),
 }
 
@@ -18,8 +26,21 @@ pub(crate) struct LocationInFileFragment {
 }
 
 impl Location {
+    pub(crate) fn at(path: Rc<String>, position: &Position) -> Location {
+        if position.line == UNKNOWN_POSITION.line {
+            Location::InFile(path)
+        } else {
+            Location::InFileFragment(LocationInFileFragment {
+                column: position.column,
+                line: position.line,
+                path,
+            })
+        }
+    }
+
     pub(crate) fn as_path(&self) -> &str {
         match &self {
+            Location::InFile(path) => path.as_str(),
             Location::InFileFragment(in_file_fragment) => {
                 in_file_fragment.path.as_str()
             }
@@ -28,6 +49,7 @@ impl Location {
 
     pub(crate) fn snippet(&self, context: usize) -> Option<String> {
         match &self {
+            Location::InFile(_) => None,
             Location::InFileFragment(fragment) => {
                 let path = fragment.path.as_str();
                 let mut file = match std::fs::File::open(path) {
@@ -61,7 +83,7 @@ impl Location {
                     None
                 } else {
                     for _ in 0..(fragment.column + width + 4) {
-                        snippet.push_str(" ");
+                        snippet.push(' ');
                     }
                     snippet.push_str("^\n");
 
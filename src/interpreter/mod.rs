@@ -5,8 +5,11 @@
 mod bindings;
 pub(crate) mod build_ast;
 pub(crate) mod error;
-mod location;
+mod float;
+pub(crate) mod location;
+mod primops;
 pub(crate) mod runtime;
 mod runtime_stack_frame;
 pub(crate) mod scope;
+mod thunk;
 pub(crate) mod value;
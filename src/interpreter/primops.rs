@@ -0,0 +1,264 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+mod attribute_set;
+mod evaluation;
+mod json;
+mod list;
+mod string;
+mod types;
+
+use std::rc::Rc;
+
+use crate::interpreter::error::Error;
+use crate::interpreter::location::Location;
+use crate::interpreter::runtime::Runtime;
+use crate::interpreter::value::Value;
+
+pub(crate) type Implementation =
+    fn(&mut Runtime, Vec<Rc<Value>>, &Location) -> Result<Rc<Value>, Error>;
+
+/// A function implemented by the interpreter.
+pub(crate) struct PrimOp {
+    pub(crate) name:           &'static str,
+    /// How many arguments it takes before it can be called,
+    /// it is curried over them.
+    ///
+    /// Internal primops with a variable number of operands,
+    /// like string interpolation, take them in a list.
+    pub(crate) arity:          usize,
+    /// Whether the arguments are forced to weak head normal form
+    /// before calling the implementation.
+    ///
+    /// Lazy primops receive thunks and force what they need.
+    pub(crate) strict:         bool,
+    pub(crate) visibility:     Visibility,
+    pub(crate) implementation: Implementation,
+}
+
+pub(crate) enum Visibility {
+    /// Only reachable through the syntax, like `a + b`.
+    Internal,
+    /// As `builtins.name`.
+    Builtins,
+    /// As `builtins.name` and as a global `name`.
+    Global,
+}
+
+/// Values exposed as `builtins.name` and as a global `name`.
+pub(crate) fn constants() -> Vec<(&'static str, Value)> {
+    vec![
+        ("false", Value::Boolean(false)),
+        ("null", Value::Null),
+        ("true", Value::Boolean(true)),
+    ]
+}
+
+pub(crate) fn primops() -> Vec<PrimOp> {
+    use Visibility::*;
+
+    let primop = |name, arity, strict, visibility, implementation| PrimOp {
+        name,
+        arity,
+        strict,
+        visibility,
+        implementation,
+    };
+
+    vec![
+        primop("abort", 1, true, Global, Runtime::built_in_abort),
+        primop("add", 2, true, Builtins, Runtime::built_in_add),
+        primop(
+            "addErrorContext",
+            2,
+            false,
+            Builtins,
+            Runtime::built_in_add_error_context,
+        ),
+        primop("all", 2, true, Builtins, Runtime::built_in_all),
+        primop("any", 2, true, Builtins, Runtime::built_in_any),
+        primop("attrNames", 1, true, Builtins, Runtime::built_in_attr_names),
+        primop("attrValues", 1, true, Builtins, Runtime::built_in_attr_values),
+        primop("baseNameOf", 1, true, Global, Runtime::built_in_base_name_of),
+        primop("built-in !", 1, false, Internal, Runtime::built_in_not),
+        primop(
+            "built-in !=",
+            2,
+            false,
+            Internal,
+            Runtime::built_in_not_equal_to,
+        ),
+        primop("built-in &&", 2, false, Internal, Runtime::built_in_and),
+        primop(
+            "built-in *",
+            2,
+            true,
+            Internal,
+            Runtime::built_in_multiplication,
+        ),
+        primop("built-in +", 2, true, Internal, Runtime::built_in_addition),
+        primop(
+            "built-in ++",
+            2,
+            true,
+            Internal,
+            Runtime::built_in_concatenation,
+        ),
+        primop("built-in -", 2, true, Internal, Runtime::built_in_subtraction),
+        primop(
+            "built-in ->",
+            2,
+            false,
+            Internal,
+            Runtime::built_in_implication,
+        ),
+        primop("built-in /", 2, true, Internal, Runtime::built_in_division),
+        primop("built-in <", 2, true, Internal, Runtime::built_in_less_than),
+        primop(
+            "built-in <=",
+            2,
+            true,
+            Internal,
+            Runtime::built_in_less_than_or_equal_to,
+        ),
+        primop("built-in //", 2, true, Internal, Runtime::built_in_update),
+        primop("built-in ==", 2, false, Internal, Runtime::built_in_equal_to),
+        primop("built-in >", 2, true, Internal, Runtime::built_in_greater_than),
+        primop(
+            "built-in >=",
+            2,
+            true,
+            Internal,
+            Runtime::built_in_greater_than_or_equal_to,
+        ),
+        primop("built-in assert", 2, false, Internal, Runtime::built_in_assert),
+        primop(
+            "built-in dynamic attributes",
+            2,
+            false,
+            Internal,
+            Runtime::built_in_dynamic_attributes,
+        ),
+        primop("built-in if", 3, false, Internal, Runtime::built_in_if),
+        primop(
+            "built-in interpolation",
+            1,
+            false,
+            Internal,
+            Runtime::built_in_interpolation,
+        ),
+        primop(
+            "built-in path interpolation",
+            1,
+            false,
+            Internal,
+            Runtime::built_in_path_interpolation,
+        ),
+        primop(
+            "built-in unary -",
+            1,
+            true,
+            Internal,
+            Runtime::built_in_negation,
+        ),
+        primop("built-in ||", 2, false, Internal, Runtime::built_in_or),
+        primop("catAttrs", 2, true, Builtins, Runtime::built_in_cat_attrs),
+        primop("concatMap", 2, true, Builtins, Runtime::built_in_concat_map),
+        primop(
+            "concatStringsSep",
+            2,
+            true,
+            Builtins,
+            Runtime::built_in_concat_strings_sep,
+        ),
+        primop("deepSeq", 2, false, Builtins, Runtime::built_in_deep_seq),
+        primop("dirOf", 1, true, Global, Runtime::built_in_dir_of),
+        primop("div", 2, true, Builtins, Runtime::built_in_division),
+        primop("elemAt", 2, true, Builtins, Runtime::built_in_elem_at),
+        primop("filter", 2, true, Builtins, Runtime::built_in_filter),
+        primop("foldl'", 3, true, Builtins, Runtime::built_in_foldl_strict),
+        primop("fromJSON", 1, true, Builtins, Runtime::built_in_from_json),
+        primop(
+            "functionArgs",
+            1,
+            true,
+            Builtins,
+            Runtime::built_in_function_args,
+        ),
+        primop("genList", 2, false, Builtins, Runtime::built_in_gen_list),
+        primop("getAttr", 2, true, Builtins, Runtime::built_in_get_attr),
+        primop("hasAttr", 2, true, Builtins, Runtime::built_in_has_attr),
+        primop("hashString", 2, true, Builtins, Runtime::built_in_hash_string),
+        primop("import", 1, true, Global, Runtime::built_in_import),
+        primop(
+            "intersectAttrs",
+            2,
+            true,
+            Builtins,
+            Runtime::built_in_intersect_attrs,
+        ),
+        primop("isAttrs", 1, true, Builtins, Runtime::built_in_is_attrs),
+        primop("isBool", 1, true, Builtins, Runtime::built_in_is_bool),
+        primop("isFloat", 1, true, Builtins, Runtime::built_in_is_float),
+        primop("isFunction", 1, true, Builtins, Runtime::built_in_is_function),
+        primop("isInt", 1, true, Builtins, Runtime::built_in_is_int),
+        primop("isList", 1, true, Builtins, Runtime::built_in_is_list),
+        primop("isNull", 1, true, Global, Runtime::built_in_is_null),
+        primop("isPath", 1, true, Builtins, Runtime::built_in_is_path),
+        primop("isString", 1, true, Builtins, Runtime::built_in_is_string),
+        primop("length", 1, true, Builtins, Runtime::built_in_length),
+        primop("lessThan", 2, true, Builtins, Runtime::built_in_less_than),
+        primop(
+            "listToAttrs",
+            1,
+            true,
+            Builtins,
+            Runtime::built_in_list_to_attrs,
+        ),
+        primop("map", 2, false, Global, Runtime::built_in_map),
+        primop("mapAttrs", 2, false, Builtins, Runtime::built_in_map_attrs),
+        primop("match", 2, true, Builtins, Runtime::built_in_match),
+        primop("mul", 2, true, Builtins, Runtime::built_in_multiplication),
+        primop("removeAttrs", 2, true, Global, Runtime::built_in_remove_attrs),
+        primop(
+            "replaceStrings",
+            3,
+            true,
+            Builtins,
+            Runtime::built_in_replace_strings,
+        ),
+        primop("seq", 2, false, Builtins, Runtime::built_in_seq),
+        primop("sort", 2, true, Builtins, Runtime::built_in_sort),
+        primop("split", 2, true, Builtins, Runtime::built_in_split),
+        primop(
+            "stringLength",
+            1,
+            true,
+            Builtins,
+            Runtime::built_in_string_length,
+        ),
+        primop("sub", 2, true, Builtins, Runtime::built_in_subtraction),
+        primop("substring", 3, true, Builtins, Runtime::built_in_substring),
+        primop("throw", 1, true, Global, Runtime::built_in_throw),
+        primop("toJSON", 1, true, Builtins, Runtime::built_in_to_json),
+        primop("toString", 1, true, Global, Runtime::built_in_to_string),
+        primop("trace", 2, false, Builtins, Runtime::built_in_trace),
+        primop(
+            "traceVerbose",
+            2,
+            false,
+            Builtins,
+            Runtime::built_in_trace_verbose,
+        ),
+        primop("tryEval", 1, false, Builtins, Runtime::built_in_try_eval),
+        primop("typeOf", 1, true, Builtins, Runtime::built_in_type_of),
+        primop(
+            "zipAttrsWith",
+            2,
+            false,
+            Builtins,
+            Runtime::built_in_zip_attrs_with,
+        ),
+    ]
+}
@@ -0,0 +1,249 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use crate::interpreter::error::Error;
+use crate::interpreter::location::Location;
+use crate::interpreter::runtime::Runtime;
+use crate::interpreter::value::Value;
+
+impl Runtime {
+    /// The names are sorted, like the attribute set itself.
+    pub(crate) fn built_in_attr_names(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let attributes =
+            self.argument(1, args.remove(0), location, Runtime::attribute_set)?;
+
+        Ok(Rc::new(Value::List(
+            attributes.into_keys().map(|name| Rc::new(string(name))).collect(),
+        )))
+    }
+
+    /// The values are sorted by the name of their attribute.
+    pub(crate) fn built_in_attr_values(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let attributes =
+            self.argument(1, args.remove(0), location, Runtime::attribute_set)?;
+
+        Ok(Rc::new(Value::List(attributes.into_values().collect())))
+    }
+
+    pub(crate) fn built_in_cat_attrs(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let name =
+            self.argument(1, args.remove(0), location, Runtime::string)?;
+
+        let mut values = Vec::new();
+        for element in elements {
+            if let Some(value) =
+                self.attribute_set(element, location)?.remove(&name)
+            {
+                values.push(value);
+            }
+        }
+
+        Ok(Rc::new(Value::List(values)))
+    }
+
+    pub(crate) fn built_in_get_attr(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let attributes =
+            self.argument(2, args.remove(1), location, Runtime::attribute_set)?;
+        let name =
+            self.argument(1, args.remove(0), location, Runtime::string)?;
+
+        self.required_attribute(&attributes, &name, location)
+    }
+
+    pub(crate) fn built_in_has_attr(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let attributes =
+            self.argument(2, args.remove(1), location, Runtime::attribute_set)?;
+        let name =
+            self.argument(1, args.remove(0), location, Runtime::string)?;
+
+        Ok(Rc::new(Value::Boolean(attributes.contains_key(&name))))
+    }
+
+    /// The attributes of the second set whose names are in the first one.
+    pub(crate) fn built_in_intersect_attrs(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs =
+            self.argument(2, args.remove(1), location, Runtime::attribute_set)?;
+        let lhs =
+            self.argument(1, args.remove(0), location, Runtime::attribute_set)?;
+
+        Ok(Rc::new(Value::AttributeSet(
+            rhs.into_iter()
+                .filter(|(name, _)| lhs.contains_key(name))
+                .collect(),
+        )))
+    }
+
+    /// The first definition of a name wins.
+    pub(crate) fn built_in_list_to_attrs(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(1, args.remove(0), location, Runtime::list)?;
+
+        let mut attributes = BTreeMap::new();
+        for element in elements {
+            let element = self.attribute_set(element, location)?;
+            let name = self.required_attribute(&element, "name", location)?;
+            let name = self.string(name, location)?;
+            let value = self.required_attribute(&element, "value", location)?;
+
+            attributes.entry(name).or_insert(value);
+        }
+
+        Ok(Rc::new(Value::AttributeSet(attributes)))
+    }
+
+    /// The function is not forced, every value of the result
+    /// is a deferred call with the name and the value of the attribute.
+    pub(crate) fn built_in_map_attrs(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let attributes =
+            self.argument(2, args.remove(1), location, Runtime::attribute_set)?;
+        let function = args.remove(0);
+
+        Ok(Rc::new(Value::AttributeSet(
+            attributes
+                .into_iter()
+                .map(|(name, value)| {
+                    let application = Value::suspended_application(
+                        function.clone(),
+                        vec![Rc::new(string(name.clone())), value],
+                        location.clone(),
+                    );
+
+                    (name, Rc::new(application))
+                })
+                .collect(),
+        )))
+    }
+
+    pub(crate) fn built_in_remove_attrs(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let names =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let mut attributes =
+            self.argument(1, args.remove(0), location, Runtime::attribute_set)?;
+
+        for name in names {
+            attributes.remove(&self.string(name, location)?);
+        }
+
+        Ok(Rc::new(Value::AttributeSet(attributes)))
+    }
+
+    /// `lhs // rhs`, the attributes of `rhs` win.
+    pub(crate) fn built_in_update(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let mut attributes = self.attribute_set(lhs, location)?;
+        attributes.extend(self.attribute_set(rhs, location)?);
+
+        Ok(Rc::new(Value::AttributeSet(attributes)))
+    }
+
+    /// The function is not forced, every value of the result
+    /// is a deferred call with the name of the attribute
+    /// and the list of its values in the sets that define it.
+    pub(crate) fn built_in_zip_attrs_with(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let function = args.remove(0);
+
+        let mut zipped: BTreeMap<String, Vec<Rc<Value>>> = BTreeMap::new();
+        for element in elements {
+            for (name, value) in self.attribute_set(element, location)? {
+                zipped.entry(name).or_default().push(value);
+            }
+        }
+
+        Ok(Rc::new(Value::AttributeSet(
+            zipped
+                .into_iter()
+                .map(|(name, values)| {
+                    let application = Value::suspended_application(
+                        function.clone(),
+                        vec![
+                            Rc::new(string(name.clone())),
+                            Rc::new(Value::List(values)),
+                        ],
+                        location.clone(),
+                    );
+
+                    (name, Rc::new(application))
+                })
+                .collect(),
+        )))
+    }
+
+    fn required_attribute(
+        &self,
+        attributes: &BTreeMap<String, Rc<Value>>,
+        name: &str,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        match attributes.get(name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(Error::Interpreter {
+                description: format!(
+                    "attribute {name:?} missing, available attributes are: \
+                     {:?}",
+                    attributes.keys().collect::<Vec<_>>(),
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+}
+
+fn string(value: String) -> Value {
+    Value::String { context: BTreeSet::new(), value }
+}
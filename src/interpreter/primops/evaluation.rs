@@ -0,0 +1,170 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::interpreter::error::Error;
+use crate::interpreter::location::Location;
+use crate::interpreter::runtime::Coercion;
+use crate::interpreter::runtime::Runtime;
+use crate::interpreter::value::Value;
+
+/// The `log` target of `trace` and `traceVerbose`,
+/// so that their messages can be told apart from the ones of the interpreter.
+pub(crate) const TRACE_TARGET: &str = "toros::trace";
+
+impl Runtime {
+    /// Stop the evaluation, even inside `tryEval`.
+    pub(crate) fn built_in_abort(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (message, _) = self.coerce_to_string(
+            args.remove(0),
+            Coercion::Interpolation,
+            location,
+        )?;
+
+        Err(Error::Abort {
+            description: format!(
+                "evaluation aborted with the following error message: \
+                 {message:?}"
+            ),
+            location:    location.clone(),
+            stack:       self.stack.clone(),
+        })
+    }
+
+    /// Force the second argument with the first one,
+    /// a message, as an additional frame of the errors it raises.
+    pub(crate) fn built_in_add_error_context(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let expression = args.remove(1);
+        let (context, _) = self.coerce_to_string(
+            args.remove(0),
+            Coercion::Interpolation,
+            location,
+        )?;
+
+        self.add_stack_frame(context, location.clone());
+        let value = self.advance_monotonically(expression);
+        self.remove_stack_frame();
+
+        value
+    }
+
+    /// Like `seq`, but the first argument is forced entirely,
+    /// including the elements of lists and the values of attribute sets.
+    pub(crate) fn built_in_deep_seq(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        self.advance_deeply(args.remove(0), location)?;
+
+        Ok(args.remove(0))
+    }
+
+    /// Force the first argument to weak head normal form
+    /// and return the second one.
+    pub(crate) fn built_in_seq(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        self.advance_monotonically(args.remove(0))?;
+
+        Ok(args.remove(0))
+    }
+
+    /// Log the first argument, forced entirely, and return the second one.
+    pub(crate) fn built_in_trace(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let value = args.remove(1);
+        let message = self.advance_deeply(args.remove(0), location)?;
+
+        log::info!(target: TRACE_TARGET, "trace: {}", traced(&message));
+
+        Ok(value)
+    }
+
+    /// Like `trace`, but logged only when debugging,
+    /// and the message is not forced otherwise.
+    pub(crate) fn built_in_trace_verbose(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let value = args.remove(1);
+
+        if log::log_enabled!(target: TRACE_TARGET, log::Level::Debug) {
+            let message = self.advance_deeply(args.remove(0), location)?;
+
+            log::debug!(target: TRACE_TARGET, "trace: {}", traced(&message));
+        }
+
+        Ok(value)
+    }
+
+    /// Fail with a message that `tryEval` can recover from.
+    pub(crate) fn built_in_throw(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (message, _) = self.coerce_to_string(
+            args.remove(0),
+            Coercion::Interpolation,
+            location,
+        )?;
+
+        Err(Error::Throw {
+            description: message,
+            location:    location.clone(),
+            stack:       self.stack.clone(),
+        })
+    }
+
+    /// Force the argument to weak head normal form and return
+    /// `{ success = true; value = ...; }`, or if that throws or fails
+    /// an assertion, `{ success = false; value = false; }`.
+    ///
+    /// Other errors, like an infinite recursion, are not caught.
+    pub(crate) fn built_in_try_eval(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (success, value) = match self.advance_monotonically(args.remove(0))
+        {
+            Ok(value) => (true, value),
+            Err(error) if error.is_catchable() => {
+                log::debug!("tryEval caught: {error}");
+                (false, Rc::new(Value::Boolean(false)))
+            }
+            Err(error) => return Err(error),
+        };
+
+        Ok(Rc::new(Value::AttributeSet(BTreeMap::from([
+            ("success".to_string(), Rc::new(Value::Boolean(success))),
+            ("value".to_string(), value),
+        ]))))
+    }
+}
+
+/// Strings are logged as they are, other values like Nix prints them.
+fn traced(message: &Value) -> String {
+    match message {
+        Value::String { value, .. } => value.clone(),
+        message => message.to_string(),
+    }
+}
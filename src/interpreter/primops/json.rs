@@ -0,0 +1,532 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::interpreter::error::Error;
+use crate::interpreter::float::Float;
+use crate::interpreter::location::Location;
+use crate::interpreter::runtime::Coercion;
+use crate::interpreter::runtime::Runtime;
+use crate::interpreter::value::Value;
+
+impl Runtime {
+    /// Parse a JSON document into values that are already forced.
+    pub(crate) fn built_in_from_json(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let json =
+            self.argument(1, args.remove(0), location, Runtime::string)?;
+
+        let mut parser = Parser { chars: json.chars().collect(), index: 0 };
+
+        parser.document().map(Rc::new).map_err(|reason| {
+            let (line, column) = parser.line_and_column();
+
+            Error::Interpreter {
+                description: format!(
+                    "parsing JSON is not possible, at line {line} column \
+                     {column}: {reason}"
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }
+        })
+    }
+
+    /// Print a value, forced entirely, as JSON without any whitespace,
+    /// the same way Nix does.
+    pub(crate) fn built_in_to_json(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let mut context = BTreeSet::new();
+        let mut json = String::new();
+
+        self.print_json(args.remove(0), &mut json, &mut context, location)?;
+
+        Ok(Rc::new(Value::String { context, value: json }))
+    }
+
+    fn print_json(
+        &mut self,
+        value: Rc<Value>,
+        json: &mut String,
+        context: &mut BTreeSet<String>,
+        location: &Location,
+    ) -> Result<(), Error> {
+        let value = self.advance_monotonically(value)?;
+
+        match &*value {
+            Value::AttributeSet(attributes)
+                if attributes.contains_key("__toString") =>
+            {
+                let (string, mut string_context) = self.coerce_to_string(
+                    value.clone(),
+                    Coercion::KeepPaths,
+                    location,
+                )?;
+
+                context.append(&mut string_context);
+                escape(&string, json);
+            }
+            Value::AttributeSet(attributes)
+                if attributes.contains_key("outPath") =>
+            {
+                self.nested(location, |runtime| {
+                    runtime.print_json(
+                        attributes["outPath"].clone(),
+                        json,
+                        context,
+                        location,
+                    )
+                })?;
+            }
+            Value::AttributeSet(attributes) => {
+                json.push('{');
+                for (index, (name, value)) in attributes.iter().enumerate() {
+                    if index > 0 {
+                        json.push(',');
+                    }
+                    escape(name, json);
+                    json.push(':');
+                    self.nested(location, |runtime| {
+                        runtime.print_json(
+                            value.clone(),
+                            json,
+                            context,
+                            location,
+                        )
+                    })?;
+                }
+                json.push('}');
+            }
+            Value::Boolean(value) => write!(json, "{value}").unwrap(),
+            Value::Float(Float(value)) => float(*value, json),
+            Value::Int(value) => write!(json, "{value}").unwrap(),
+            Value::List(elements) => {
+                json.push('[');
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        json.push(',');
+                    }
+                    self.nested(location, |runtime| {
+                        runtime.print_json(
+                            element.clone(),
+                            json,
+                            context,
+                            location,
+                        )
+                    })?;
+                }
+                json.push(']');
+            }
+            Value::Null => json.push_str("null"),
+            Value::Path(_) | Value::String { .. } => {
+                let (string, mut string_context) = self.coerce_to_string(
+                    value.clone(),
+                    Coercion::Interpolation,
+                    location,
+                )?;
+
+                context.append(&mut string_context);
+                escape(&string, json);
+            }
+            _ => {
+                return Err(Error::Interpreter {
+                    description: format!(
+                        "converting a {:?} to JSON is not possible",
+                        value.kind(),
+                    ),
+                    location:    location.clone(),
+                    stack:       self.stack.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Quote a string, escaping what JSON requires and nothing else.
+fn escape(string: &str, json: &mut String) {
+    json.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\u{8}' => json.push_str("\\b"),
+            '\u{c}' => json.push_str("\\f"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '\0'..='\u{1f}' => write!(json, "\\u{:04x}", char as u32).unwrap(),
+            char => json.push(char),
+        }
+    }
+    json.push('"');
+}
+
+/// Print a float like the JSON library of Nix does,
+/// with the shortest digits that read back as the same number,
+/// and always with a dot or an exponent.
+fn float(value: f64, json: &mut String) {
+    if !value.is_finite() {
+        json.push_str("null");
+        return;
+    }
+    if value.is_sign_negative() {
+        json.push('-');
+    }
+    if value == 0.0 {
+        json.push_str("0.0");
+        return;
+    }
+
+    // Like `15e-1`, the digits are all before the dot
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // Where the dot goes, counting from the first digit
+    let n = exponent.parse::<i32>().unwrap() + 1;
+
+    if k <= n && n <= 15 {
+        json.push_str(&digits);
+        json.extend(std::iter::repeat_n('0', (n - k) as usize));
+        json.push_str(".0");
+    } else if 0 < n && n <= 15 {
+        json.push_str(&digits[..n as usize]);
+        json.push('.');
+        json.push_str(&digits[n as usize..]);
+    } else if -4 < n && n <= 0 {
+        json.push_str("0.");
+        json.extend(std::iter::repeat_n('0', -n as usize));
+        json.push_str(&digits);
+    } else {
+        json.push_str(&digits[..1]);
+        if k > 1 {
+            json.push('.');
+            json.push_str(&digits[1..]);
+        }
+        let exponent = n - 1;
+        let sign = if exponent < 0 { '-' } else { '+' };
+        write!(json, "e{sign}{:02}", exponent.abs()).unwrap();
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl Parser {
+    /// Where the parser is, both starting at 1.
+    fn line_and_column(&self) -> (usize, usize) {
+        let before = &self.chars[..self.index.min(self.chars.len())];
+        let line = before.iter().filter(|char| **char == '\n').count() + 1;
+        let column = match before.iter().rposition(|char| *char == '\n') {
+            Some(newline) => before.len() - newline,
+            None => before.len() + 1,
+        };
+
+        (line, column)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.index += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(char) if char == expected => {
+                self.index += 1;
+                Ok(())
+            }
+            char => Err(unexpected(char, &format!("{expected:?}"))),
+        }
+    }
+
+    fn document(&mut self) -> Result<Value, String> {
+        let value = self.value()?;
+
+        self.whitespace();
+        match self.peek() {
+            None => Ok(value),
+            char => Err(unexpected(char, "the end of the input")),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.whitespace();
+
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Value::String {
+                context: BTreeSet::new(),
+                value:   self.string()?,
+            }),
+            Some('-' | '0'..='9') => self.number(),
+            Some('f') => self.literal("false", Value::Boolean(false)),
+            Some('n') => self.literal("null", Value::Null),
+            Some('t') => self.literal("true", Value::Boolean(true)),
+            char => Err(unexpected(char, "a value")),
+        }
+    }
+
+    fn literal(
+        &mut self,
+        literal: &str,
+        value: Value,
+    ) -> Result<Value, String> {
+        for expected in literal.chars() {
+            match self.peek() {
+                Some(char) if char == expected => self.index += 1,
+                char => return Err(unexpected(char, &format!("{literal:?}"))),
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Later definitions of an attribute win.
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        self.whitespace();
+
+        let mut attributes = BTreeMap::new();
+        if self.peek() == Some('}') {
+            self.index += 1;
+            return Ok(Value::AttributeSet(attributes));
+        }
+
+        loop {
+            self.whitespace();
+            if self.peek() != Some('"') {
+                return Err(unexpected(self.peek(), "an attribute name"));
+            }
+            let name = self.string()?;
+
+            self.whitespace();
+            self.expect(':')?;
+            attributes.insert(name, Rc::new(self.value()?));
+
+            self.whitespace();
+            match self.peek() {
+                Some(',') => self.index += 1,
+                Some('}') => {
+                    self.index += 1;
+                    return Ok(Value::AttributeSet(attributes));
+                }
+                char => return Err(unexpected(char, "',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        self.whitespace();
+
+        let mut elements = Vec::new();
+        if self.peek() == Some(']') {
+            self.index += 1;
+            return Ok(Value::List(elements));
+        }
+
+        loop {
+            elements.push(Rc::new(self.value()?));
+
+            self.whitespace();
+            match self.peek() {
+                Some(',') => self.index += 1,
+                Some(']') => {
+                    self.index += 1;
+                    return Ok(Value::List(elements));
+                }
+                char => return Err(unexpected(char, "',' or ']'")),
+            }
+        }
+    }
+
+    /// Integers that do not fit in an Int are read as floats,
+    /// unless they are positive and fit in 64 unsigned bits, like in Nix.
+    /// Floats that do not fit in a Float are an error.
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.index;
+        let mut integer = true;
+
+        if self.peek() == Some('-') {
+            self.index += 1;
+        }
+        match self.peek() {
+            Some('0') => self.index += 1,
+            Some('1'..='9') => self.digits(),
+            char => return Err(unexpected(char, "a digit")),
+        }
+        if self.peek() == Some('.') {
+            integer = false;
+            self.index += 1;
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(unexpected(self.peek(), "a digit"));
+            }
+            self.digits();
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            integer = false;
+            self.index += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.index += 1;
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(unexpected(self.peek(), "a digit"));
+            }
+            self.digits();
+        }
+
+        let number: String = self.chars[start..self.index].iter().collect();
+
+        if integer {
+            if let Ok(value) = number.parse::<i64>() {
+                return Ok(Value::Int(value));
+            }
+            if number.parse::<u64>().is_ok() {
+                self.index = start;
+                return Err(format!(
+                    "the number {number} is out of the range of an Int"
+                ));
+            }
+        }
+
+        match number.parse::<f64>().unwrap() {
+            value if value.is_finite() => Ok(Value::Float(Float(value))),
+            _ => {
+                self.index = start;
+                Err(format!(
+                    "the number {number} is out of the range of a Float"
+                ))
+            }
+        }
+    }
+
+    fn digits(&mut self) {
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.index += 1;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut string = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.index += 1;
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    self.index += 1;
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.index += 1;
+                            string.push(self.code_point()?);
+                            continue;
+                        }
+                        char => return Err(unexpected(char, "an escape")),
+                    };
+                    self.index += 1;
+                    string.push(escaped);
+                }
+                Some(char @ '\0'..='\u{1f}') => {
+                    return Err(format!(
+                        "the control character {char:?} must be escaped"
+                    ));
+                }
+                Some(char) => {
+                    self.index += 1;
+                    string.push(char);
+                }
+                None => return Err(unexpected(None, "'\"'")),
+            }
+        }
+    }
+
+    /// The rest of a `\uXXXX` escape,
+    /// and of the second half of a surrogate pair if needed.
+    fn code_point(&mut self) -> Result<char, String> {
+        let high = self.hexadecimal()?;
+
+        let code_point = match high {
+            0xd800..=0xdbff => {
+                if self.chars.get(self.index..self.index + 2)
+                    != Some(&['\\', 'u'])
+                {
+                    return Err("a surrogate pair is incomplete".to_string());
+                }
+                self.index += 2;
+
+                match self.hexadecimal()? {
+                    low @ 0xdc00..=0xdfff => {
+                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                    }
+                    _ => {
+                        return Err("a surrogate pair is invalid".to_string());
+                    }
+                }
+            }
+            0xdc00..=0xdfff => {
+                return Err("a surrogate pair is invalid".to_string());
+            }
+            code_point => code_point,
+        };
+
+        Ok(char::from_u32(code_point).unwrap())
+    }
+
+    fn hexadecimal(&mut self) -> Result<u32, String> {
+        let mut value = 0;
+
+        for _ in 0..4 {
+            match self.peek().and_then(|char| char.to_digit(16)) {
+                Some(digit) => {
+                    self.index += 1;
+                    value = value * 16 + digit;
+                }
+                None => {
+                    return Err(unexpected(self.peek(), "a hexadecimal digit"));
+                }
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+fn unexpected(char: Option<char>, expected: &str) -> String {
+    match char {
+        Some(char) => format!("expected {expected} but found {char:?}"),
+        None => format!("expected {expected} but the input ended"),
+    }
+}
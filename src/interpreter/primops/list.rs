@@ -0,0 +1,262 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::rc::Rc;
+
+use crate::interpreter::error::Error;
+use crate::interpreter::location::Location;
+use crate::interpreter::runtime::Runtime;
+use crate::interpreter::value::Value;
+
+impl Runtime {
+    pub(crate) fn built_in_all(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let predicate = args.remove(0);
+
+        for element in elements {
+            let result =
+                self.call(predicate.clone(), vec![element], location)?;
+
+            if !self.boolean(result, location)? {
+                return Ok(Rc::new(Value::Boolean(false)));
+            }
+        }
+
+        Ok(Rc::new(Value::Boolean(true)))
+    }
+
+    pub(crate) fn built_in_any(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let predicate = args.remove(0);
+
+        for element in elements {
+            let result =
+                self.call(predicate.clone(), vec![element], location)?;
+
+            if self.boolean(result, location)? {
+                return Ok(Rc::new(Value::Boolean(true)));
+            }
+        }
+
+        Ok(Rc::new(Value::Boolean(false)))
+    }
+
+    pub(crate) fn built_in_concat_map(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let function = args.remove(0);
+
+        let mut concatenated = Vec::new();
+        for element in elements {
+            let result =
+                self.call(function.clone(), vec![element], location)?;
+
+            concatenated.extend(self.list(result, location)?);
+        }
+
+        Ok(Rc::new(Value::List(concatenated)))
+    }
+
+    pub(crate) fn built_in_elem_at(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let index = self.argument(2, args.remove(1), location, Runtime::int)?;
+        let elements =
+            self.argument(1, args.remove(0), location, Runtime::list)?;
+
+        match usize::try_from(index).ok().and_then(|index| elements.get(index))
+        {
+            Some(element) => Ok(element.clone()),
+            None => Err(Error::Interpreter {
+                description: format!("list index {index} is out of bounds"),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn built_in_filter(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let predicate = args.remove(0);
+
+        let mut filtered = Vec::new();
+        for element in elements {
+            let result =
+                self.call(predicate.clone(), vec![element.clone()], location)?;
+
+            if self.boolean(result, location)? {
+                filtered.push(element);
+            }
+        }
+
+        Ok(Rc::new(Value::List(filtered)))
+    }
+
+    /// Unlike `foldl`, the accumulator is forced at every step,
+    /// so long lists do not build a chain of deferred calls.
+    pub(crate) fn built_in_foldl_strict(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(3, args.remove(2), location, Runtime::list)?;
+        let mut accumulator = args.remove(1);
+        let function = args.remove(0);
+
+        for element in elements {
+            accumulator = self.call(
+                function.clone(),
+                vec![accumulator, element],
+                location,
+            )?;
+        }
+
+        Ok(accumulator)
+    }
+
+    /// The elements are deferred calls of the generator,
+    /// which is not forced until an element is.
+    pub(crate) fn built_in_gen_list(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let length =
+            self.argument(2, args.remove(1), location, Runtime::int)?;
+        let generator = args.remove(0);
+
+        if length < 0 {
+            return Err(Error::Interpreter {
+                description: format!("cannot create list of size {length}"),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            });
+        }
+
+        Ok(Rc::new(Value::List(
+            (0..length)
+                .map(|index| {
+                    Rc::new(Value::suspended_application(
+                        generator.clone(),
+                        vec![Rc::new(Value::Int(index))],
+                        location.clone(),
+                    ))
+                })
+                .collect(),
+        )))
+    }
+
+    pub(crate) fn built_in_length(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(1, args.remove(0), location, Runtime::list)?;
+
+        Ok(Rc::new(Value::Int(elements.len() as i64)))
+    }
+
+    /// Neither the function nor the elements are forced,
+    /// every element of the result is a deferred call.
+    pub(crate) fn built_in_map(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let function = args.remove(0);
+
+        Ok(Rc::new(Value::List(
+            elements
+                .into_iter()
+                .map(|element| {
+                    Rc::new(Value::suspended_application(
+                        function.clone(),
+                        vec![element],
+                        location.clone(),
+                    ))
+                })
+                .collect(),
+        )))
+    }
+
+    /// A stable sort, elements the comparator does not order
+    /// keep their relative order.
+    pub(crate) fn built_in_sort(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let comparator = args.remove(0);
+
+        let sorted = self.merge_sort(elements, &comparator, location)?;
+
+        Ok(Rc::new(Value::List(sorted)))
+    }
+
+    fn merge_sort(
+        &mut self,
+        mut elements: Vec<Rc<Value>>,
+        comparator: &Rc<Value>,
+        location: &Location,
+    ) -> Result<Vec<Rc<Value>>, Error> {
+        if elements.len() <= 1 {
+            return Ok(elements);
+        }
+
+        let right = elements.split_off(elements.len() / 2);
+        let right = self.merge_sort(right, comparator, location)?;
+        let left = self.merge_sort(elements, comparator, location)?;
+
+        let mut merged = Vec::with_capacity(left.len() + right.len());
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+
+        while let (Some(lhs), Some(rhs)) = (left.peek(), right.peek()) {
+            // Taking from the left unless the right is strictly less
+            // is what keeps the sort stable
+            let result = self.call(
+                comparator.clone(),
+                vec![rhs.clone(), lhs.clone()],
+                location,
+            )?;
+
+            if self.boolean(result, location)? {
+                merged.extend(right.next());
+            } else {
+                merged.extend(left.next());
+            }
+        }
+        merged.extend(left);
+        merged.extend(right);
+
+        Ok(merged)
+    }
+}
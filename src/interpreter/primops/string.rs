@@ -0,0 +1,399 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use crate::hash::md5::Md5;
+use crate::hash::sha1::Sha1;
+use crate::hash::sha256::Sha256;
+use crate::hash::sha512::Sha512;
+use crate::interpreter::error::Error;
+use crate::interpreter::location::Location;
+use crate::interpreter::runtime::Coercion;
+use crate::interpreter::runtime::Runtime;
+use crate::interpreter::value::Value;
+use crate::regex::Captures;
+use crate::regex::Regex;
+
+impl Runtime {
+    /// What follows the last `/`, ignoring a trailing one.
+    ///
+    /// Paths are not copied to the store.
+    pub(crate) fn built_in_base_name_of(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (value, context) = self.coerce_to_string(
+            args.remove(0),
+            Coercion::KeepPaths,
+            location,
+        )?;
+
+        let trimmed = match value.strip_suffix('/') {
+            Some(trimmed) if !trimmed.is_empty() => trimmed,
+            _ => &value,
+        };
+        let base_name = match trimmed.rfind('/') {
+            Some(index) => &trimmed[index + 1..],
+            None => trimmed,
+        };
+
+        Ok(Rc::new(Value::String { context, value: base_name.to_string() }))
+    }
+
+    pub(crate) fn built_in_concat_strings_sep(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let elements =
+            self.argument(2, args.remove(1), location, Runtime::list)?;
+        let (separator, mut context) = self.argument(
+            1,
+            args.remove(0),
+            location,
+            Runtime::string_with_context,
+        )?;
+
+        let mut strings = Vec::with_capacity(elements.len());
+        for element in elements {
+            let (string, mut string_context) = self.coerce_to_string(
+                element,
+                Coercion::Interpolation,
+                location,
+            )?;
+
+            context.append(&mut string_context);
+            strings.push(string);
+        }
+
+        Ok(Rc::new(Value::String { context, value: strings.join(&separator) }))
+    }
+
+    /// Everything before the last `/`,
+    /// which is a path if the argument is a path.
+    pub(crate) fn built_in_dir_of(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let value = self.advance_monotonically(args.remove(0))?;
+
+        if let Value::Path(path) = &*value {
+            return Ok(Rc::new(Value::Path(dir_of(path).to_string())));
+        }
+
+        let (value, context) =
+            self.coerce_to_string(value, Coercion::KeepPaths, location)?;
+
+        Ok(Rc::new(Value::String {
+            context,
+            value: dir_of(&value).to_string(),
+        }))
+    }
+
+    /// The hexadecimal digest of a string.
+    pub(crate) fn built_in_hash_string(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (value, context) = self.argument(
+            2,
+            args.remove(1),
+            location,
+            Runtime::string_with_context,
+        )?;
+        let algorithm =
+            self.argument(1, args.remove(0), location, Runtime::string)?;
+
+        let digest = match algorithm.as_str() {
+            "md5" => {
+                let mut md5 = Md5::new();
+                md5.update(value.as_bytes());
+                md5.finalize().to_vec()
+            }
+            "sha1" => {
+                let mut sha1 = Sha1::new();
+                sha1.update(value.as_bytes());
+                sha1.finalize().to_vec()
+            }
+            "sha256" => {
+                let mut sha256 = Sha256::new();
+                sha256.update(value.as_bytes());
+                sha256.finalize().to_vec()
+            }
+            "sha512" => {
+                let mut sha512 = Sha512::new();
+                sha512.update(value.as_bytes());
+                sha512.finalize().to_vec()
+            }
+            _ => {
+                return Err(Error::Interpreter {
+                    description: format!(
+                        "hashing with {algorithm:?} is not possible, expected \
+                         \"md5\", \"sha1\", \"sha256\" or \"sha512\""
+                    ),
+                    location:    location.clone(),
+                    stack:       self.stack.clone(),
+                });
+            }
+        };
+
+        Ok(Rc::new(Value::String {
+            context,
+            value: digest.iter().map(|byte| format!("{byte:02x}")).collect(),
+        }))
+    }
+
+    /// The groups of the regular expression if it matches the whole string,
+    /// `null` otherwise.
+    pub(crate) fn built_in_match(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (value, context) = self.argument(
+            2,
+            args.remove(1),
+            location,
+            Runtime::string_with_context,
+        )?;
+        let regex = self.regex(args.remove(0), location)?;
+
+        match regex.full_match(&value) {
+            Some(captures) => {
+                Ok(Rc::new(Value::List(groups(&value, &context, captures))))
+            }
+            None => Ok(Rc::new(Value::Null)),
+        }
+    }
+
+    /// Replace the first of `from` found at every position of the string
+    /// with the string at the same index of `to`.
+    ///
+    /// An empty string in `from` matches between every character.
+    pub(crate) fn built_in_replace_strings(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (value, mut context) = self.argument(
+            3,
+            args.remove(2),
+            location,
+            Runtime::string_with_context,
+        )?;
+        let to = self.argument(2, args.remove(1), location, Runtime::list)?;
+        let from = self.argument(1, args.remove(0), location, Runtime::list)?;
+
+        if from.len() != to.len() {
+            return Err(Error::Interpreter {
+                description: "the lists of strings to replace and their \
+                              replacements have different lengths"
+                    .to_string(),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            });
+        }
+
+        let from = from
+            .into_iter()
+            .map(|from| self.string(from, location))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Replacements are forced the first time they are used
+        let mut replacements = vec![None; to.len()];
+
+        let mut replaced = String::new();
+        let mut position = 0;
+        while position <= value.len() {
+            let rest = &value[position..];
+            let found = from.iter().position(|from| rest.starts_with(from));
+
+            if let Some(index) = found {
+                if replacements[index].is_none() {
+                    let (replacement, replacement_context) =
+                        self.string_with_context(to[index].clone(), location)?;
+
+                    context.extend(replacement_context);
+                    replacements[index] = Some(replacement);
+                }
+                replaced.push_str(replacements[index].as_ref().unwrap());
+                position += from[index].len();
+            }
+
+            if found.is_none_or(|index| from[index].is_empty()) {
+                match rest.chars().next() {
+                    Some(char) => {
+                        replaced.push(char);
+                        position += char.len_utf8();
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        Ok(Rc::new(Value::String { context, value: replaced }))
+    }
+
+    /// The parts of the string between the matches of the regular expression,
+    /// with the groups of every match in between them.
+    pub(crate) fn built_in_split(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (value, context) = self.argument(
+            2,
+            args.remove(1),
+            location,
+            Runtime::string_with_context,
+        )?;
+        let regex = self.regex(args.remove(0), location)?;
+
+        let mut parts = Vec::new();
+        let mut end = 0;
+        for captures in regex.find_all(&value) {
+            let matched = captures[0].clone().unwrap();
+
+            parts.push(Rc::new(Value::String {
+                context: context.clone(),
+                value:   value[end..matched.start].to_string(),
+            }));
+            parts
+                .push(Rc::new(Value::List(groups(&value, &context, captures))));
+            end = matched.end;
+        }
+        parts.push(Rc::new(Value::String {
+            context,
+            value: value[end..].to_string(),
+        }));
+
+        Ok(Rc::new(Value::List(parts)))
+    }
+
+    /// The length in bytes.
+    pub(crate) fn built_in_string_length(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (value, _) = self.coerce_to_string(
+            args.remove(0),
+            Coercion::Interpolation,
+            location,
+        )?;
+
+        Ok(Rc::new(Value::Int(value.len() as i64)))
+    }
+
+    /// The bytes from `start` on, at most `length` of them,
+    /// or all of them if `length` is negative.
+    pub(crate) fn built_in_substring(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (value, context) = self.coerce_to_string(
+            args.remove(2),
+            Coercion::Interpolation,
+            location,
+        )?;
+        let length =
+            self.argument(2, args.remove(1), location, Runtime::int)?;
+        let start = self.argument(1, args.remove(0), location, Runtime::int)?;
+
+        if start < 0 {
+            return Err(Error::Interpreter {
+                description: format!(
+                    "taking a substring from the negative position {start} is \
+                     not possible"
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            });
+        }
+
+        // Positions are in bytes, like in Nix
+        let start = (start as usize).min(value.len());
+        let end = match usize::try_from(length) {
+            Ok(length) => start.saturating_add(length).min(value.len()),
+            Err(_) => value.len(),
+        };
+
+        match value.get(start..end) {
+            Some(value) => {
+                Ok(Rc::new(Value::String { context, value: value.to_string() }))
+            }
+            None => Err(Error::Interpreter {
+                description: format!(
+                    "taking the substring from {start} to {end} of {value:?} \
+                     is not possible because it splits a character"
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn built_in_to_string(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let (value, context) = self.coerce_to_string(
+            args.remove(0),
+            Coercion::ToString,
+            location,
+        )?;
+
+        Ok(Rc::new(Value::String { context, value }))
+    }
+
+    fn regex(
+        &mut self,
+        value: Rc<Value>,
+        location: &Location,
+    ) -> Result<Regex, Error> {
+        let pattern = self.argument(1, value, location, Runtime::string)?;
+
+        Regex::new(&pattern).map_err(|error| Error::Interpreter {
+            description: format!(
+                "compiling the regular expression {pattern:?} is not \
+                 possible: {error}"
+            ),
+            location:    location.clone(),
+            stack:       self.stack.clone(),
+        })
+    }
+}
+
+fn dir_of(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) => "/",
+        Some(index) => &path[..index],
+        None => ".",
+    }
+}
+
+/// The groups of a match as strings, or `null` if they did not participate.
+fn groups(
+    value: &str,
+    context: &BTreeSet<String>,
+    captures: Captures,
+) -> Vec<Rc<Value>> {
+    captures
+        .into_iter()
+        .skip(1)
+        .map(|capture| match capture {
+            Some(range) => Rc::new(Value::String {
+                context: context.clone(),
+                value:   value[range].to_string(),
+            }),
+            None => Rc::new(Value::Null),
+        })
+        .collect()
+}
@@ -0,0 +1,116 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use crate::interpreter::error::Error;
+use crate::interpreter::location::Location;
+use crate::interpreter::runtime::Runtime;
+use crate::interpreter::value::Value;
+
+impl Runtime {
+    pub(crate) fn built_in_is_attrs(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "set"))
+    }
+
+    pub(crate) fn built_in_is_bool(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "bool"))
+    }
+
+    pub(crate) fn built_in_is_float(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "float"))
+    }
+
+    pub(crate) fn built_in_is_function(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "lambda"))
+    }
+
+    pub(crate) fn built_in_is_int(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "int"))
+    }
+
+    pub(crate) fn built_in_is_list(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "list"))
+    }
+
+    pub(crate) fn built_in_is_null(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "null"))
+    }
+
+    pub(crate) fn built_in_is_path(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "path"))
+    }
+
+    pub(crate) fn built_in_is_string(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(is_type(&args[0], "string"))
+    }
+
+    pub(crate) fn built_in_type_of(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        _location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        Ok(Rc::new(Value::String {
+            context: BTreeSet::new(),
+            value:   type_of(&args[0]).to_string(),
+        }))
+    }
+}
+
+/// The name Nix gives to the type of a value in weak head normal form.
+fn type_of(value: &Value) -> &'static str {
+    match value.kind() {
+        "AttributeSet" => "set",
+        "Boolean" => "bool",
+        "Float" => "float",
+        "Int" => "int",
+        "List" => "list",
+        "Null" => "null",
+        "Path" => "path",
+        "String" => "string",
+        // Functions, built-ins and partially applied built-ins
+        _ => "lambda",
+    }
+}
+
+fn is_type(value: &Value, expected: &str) -> Rc<Value> {
+    Rc::new(Value::Boolean(type_of(value) == expected))
+}
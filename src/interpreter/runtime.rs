@@ -2,38 +2,190 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-only
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
 use std::collections::LinkedList;
 use std::rc::Rc;
 
+use nixel::ast::FunctionArgument;
+use nixel::ast::AST;
+use nixel::deps::santiago::grammar::Grammar;
+use nixel::deps::santiago::lexer::LexerRules;
+use nixel::deps::santiago::lexer::Position;
+use nixel::grammar::grammar;
+use nixel::lexer::lexer_rules;
+
 use super::runtime_stack_frame::RuntimeStackFrame;
+use crate::interpreter::bindings::AttributeName;
+use crate::interpreter::build_ast::build_ast;
 use crate::interpreter::error::Error;
+use crate::interpreter::float::Float;
 use crate::interpreter::location::Location;
+use crate::interpreter::primops::constants;
+use crate::interpreter::primops::primops;
+use crate::interpreter::primops::PrimOp;
+use crate::interpreter::primops::Visibility;
+use crate::interpreter::scope::Scope;
 use crate::interpreter::scope::ScopeKind;
+use crate::interpreter::thunk::Thunk;
+use crate::interpreter::value::canonicalize_path;
 use crate::interpreter::value::Value;
+use crate::store::path_to_store_path;
+
+/// How deeply evaluation may nest on the native stack
+/// before we give up and report a (possible) infinite recursion,
+/// like the `max-call-depth` of Nix.
+///
+/// Calls to functions, thunks forced while forcing another one,
+/// and the levels of values walked by deep forcing, comparisons
+/// or conversions to strings count one each.
+const MAX_DEPTH: usize = 10_000;
+
+/// Which values `Runtime::coerce_to_string` accepts and how.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Coercion {
+    /// Like `"${value}"`, paths are copied to the store.
+    Interpolation,
+    /// Like `baseNameOf value`, paths are kept as they are.
+    KeepPaths,
+    /// Like `toString value`, paths are kept as they are,
+    /// and Booleans, numbers, null and lists are accepted too.
+    ToString,
+}
 
-#[derive(Debug)]
 pub(crate) struct Runtime {
     pub(crate) stack: LinkedList<RuntimeStackFrame>,
+    /// How deeply evaluation is nested on the native stack.
+    depth:            usize,
+    /// The values reachable by name without being bound,
+    /// like `builtins`, `true` or `map`.
+    globals:          HashMap<String, Rc<Value>>,
+    grammar:          Grammar<AST>,
+    /// Imported files by canonical path.
+    ///
+    /// Each file is kept as a thunk of its AST in a fresh root scope,
+    /// so it is parsed once and evaluated at most once.
+    imports:          HashMap<String, Rc<Value>>,
+    lexer_rules:      LexerRules,
+    primops:          HashMap<&'static str, PrimOp>,
+    store_paths:      HashMap<String, String>,
 }
 
 impl Runtime {
     pub(crate) fn new() -> Runtime {
-        Runtime { stack: LinkedList::new() }
+        let mut builtins = BTreeMap::new();
+        let mut globals = HashMap::new();
+
+        for (name, value) in constants() {
+            let value = Rc::new(value);
+
+            builtins.insert(name.to_string(), value.clone());
+            globals.insert(name.to_string(), value);
+        }
+
+        let primops: HashMap<&'static str, PrimOp> =
+            primops().into_iter().map(|primop| (primop.name, primop)).collect();
+
+        for primop in primops.values() {
+            let value = Rc::new(Value::BuiltInFunction {
+                identifier: primop.name.to_string(),
+            });
+
+            match primop.visibility {
+                Visibility::Internal => {}
+                Visibility::Builtins => {
+                    builtins.insert(primop.name.to_string(), value);
+                }
+                Visibility::Global => {
+                    builtins.insert(primop.name.to_string(), value.clone());
+                    globals.insert(primop.name.to_string(), value);
+                }
+            }
+        }
+
+        globals.insert(
+            "builtins".to_string(),
+            Rc::new(Value::AttributeSet(builtins)),
+        );
+
+        Runtime {
+            stack: LinkedList::new(),
+            depth: 0,
+            globals,
+            grammar: grammar(),
+            imports: HashMap::new(),
+            lexer_rules: lexer_rules(),
+            primops,
+            store_paths: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn build_ast(&self, path: &str) -> Result<AST, Error> {
+        build_ast(&self.lexer_rules, &self.grammar, path)
     }
 
-    fn add_stack_frame(&mut self, description: String, location: Location) {
+    pub(crate) fn add_stack_frame(
+        &mut self,
+        description: String,
+        location: Location,
+    ) {
         let stack_frame = RuntimeStackFrame { description, location };
         log::trace!("stack += {stack_frame}");
         self.stack.push_back(stack_frame);
     }
 
+    pub(crate) fn remove_stack_frame(&mut self) {
+        if let Some(stack_frame) = self.stack.pop_back() {
+            log::trace!("stack -= {stack_frame}");
+        }
+    }
+
     pub(crate) fn advance(
         &mut self,
         value: &Rc<Value>,
     ) -> Result<Rc<Value>, Error> {
         let value = match &**value {
-            Value::DeferredValue { ast, path, scope } => {
-                Ok(Rc::new(Value::from_ast(path.clone(), ast.clone(), scope)))
+            Value::Thunk(thunk) => {
+                let thunk_location = thunk.borrow().location();
+                let (value, location) = match &*thunk.borrow() {
+                    Thunk::Deferred { ast, path, scope } => (
+                        Rc::new(Value::from_ast(
+                            path.clone(),
+                            ast.clone(),
+                            scope,
+                        )?),
+                        thunk_location.unwrap(),
+                    ),
+                    Thunk::InProgress { location } => {
+                        return Err(Error::Interpreter {
+                            description: "infinite recursion encountered"
+                                .to_string(),
+                            location:    location.clone(),
+                            stack:       self.stack.clone(),
+                        });
+                    }
+                    Thunk::Forced(value) => return Ok(value.clone()),
+                    Thunk::Suspended { location, value } => {
+                        (value.clone(), location.clone())
+                    }
+                };
+
+                let deferred = thunk
+                    .replace(Thunk::InProgress { location: location.clone() });
+
+                match self.nested(&location, |runtime| {
+                    runtime.advance_monotonically(value)
+                }) {
+                    Ok(value) => {
+                        thunk.replace(Thunk::Forced(value.clone()));
+                        Ok(value)
+                    }
+                    Err(error) => {
+                        thunk.replace(deferred);
+                        Err(error)
+                    }
+                }
             }
 
             Value::FunctionApplication {
@@ -42,74 +194,176 @@ impl Runtime {
                 function,
                 location,
             } => {
+                let unforced_function = function;
                 let function = self.advance_monotonically(function.clone())?;
 
                 match &*function {
-                    Value::BuiltInFunction {
-                        expected_arguments,
-                        identifier,
-                    } => {
+                    Value::BuiltInFunction { identifier } => {
+                        let (expected_arguments, strict, implementation) =
+                            match self.primops.get(identifier.as_str()) {
+                                Some(primop) => (
+                                    primop.arity,
+                                    primop.strict,
+                                    primop.implementation,
+                                ),
+                                None => {
+                                    return Err(Error::Interpreter {
+                                        description: format!(
+                                            "the built-in {identifier:?} does \
+                                             not exist"
+                                        ),
+                                        location:    location.clone(),
+                                        stack:       self.stack.clone(),
+                                    });
+                                }
+                            };
+
+                        // Not enough arguments yet, this is a partial
+                        // application that waits for the rest
+                        if arguments.len() - argument_index < expected_arguments
+                        {
+                            if Rc::ptr_eq(&function, unforced_function) {
+                                return Ok(value.clone());
+                            }
+
+                            return Ok(Rc::new(Value::FunctionApplication {
+                                argument_index: *argument_index,
+                                arguments:      arguments.clone(),
+                                function:       function.clone(),
+                                location:       location.clone(),
+                            }));
+                        }
+
                         self.add_stack_frame(
                             format!("evaluating {identifier:?}"),
                             location.clone(),
                         );
 
-                        if *expected_arguments == arguments.len() {
-                            let function = match identifier.as_str() {
-                                "built-in +" => Runtime::built_in_addition,
-                                _ => unreachable!(),
-                            };
+                        let next_index = argument_index + expected_arguments;
+                        let value = arguments[*argument_index..next_index]
+                            .iter()
+                            .map(|argument| {
+                                if strict {
+                                    self.advance_monotonically(argument.clone())
+                                } else {
+                                    Ok(argument.clone())
+                                }
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                            .and_then(|arguments| {
+                                implementation(self, arguments, location)
+                            });
+
+                        self.remove_stack_frame();
 
-                            function(self, arguments.clone(), location)
+                        // Apply the result to the remaining arguments
+                        if next_index == arguments.len() {
+                            value
                         } else {
-                            Ok(value.clone())
+                            Ok(Rc::new(Value::FunctionApplication {
+                                argument_index: next_index,
+                                arguments:      arguments.clone(),
+                                function:       value?,
+                                location:       location.clone(),
+                            }))
                         }
                     }
+                    Value::FunctionApplication {
+                        argument_index: partial_argument_index,
+                        arguments: partial_arguments,
+                        function: partial_function,
+                        ..
+                    } if matches!(
+                        &**partial_function,
+                        Value::BuiltInFunction { .. }
+                    ) =>
+                    {
+                        // A partially applied built-in receiving more arguments
+                        let mut all_arguments = partial_arguments
+                            [*partial_argument_index..]
+                            .to_vec();
+                        all_arguments
+                            .extend_from_slice(&arguments[*argument_index..]);
+
+                        Ok(Rc::new(Value::FunctionApplication {
+                            argument_index: 0,
+                            arguments:      all_arguments,
+                            function:       partial_function.clone(),
+                            location:       location.clone(),
+                        }))
+                    }
                     Value::Function {
                         bind_to,
+                        destructure_to,
+                        ellipsis,
                         implementation,
                         path,
                         scope,
-                        ..
                     } => {
+                        // Like in Nix, `f a b` is one call,
+                        // its later arguments are applied within it
+                        let call = *argument_index == 0;
+                        if call {
+                            self.check_depth(location)?;
+                            self.depth += 1;
+                        }
+
                         let arguments = arguments.clone();
                         let argument = arguments[*argument_index].clone();
-                        let argument = self.advance_monotonically(argument)?;
                         self.add_stack_frame(
                             format!(
-                                "calling a {:?} with argument #{} of kind {:?}",
+                                "calling a {:?} with argument #{}",
                                 function.kind(),
                                 argument_index + 1,
-                                argument.kind(),
                             ),
                             location.clone(),
                         );
 
                         let scope = scope.derive(ScopeKind::Plain);
 
-                        if let Some(bind_to) = bind_to {
-                            scope.bind(bind_to.clone(), argument);
-                        }
-
-                        let value = Value::DeferredValue {
-                            ast:   implementation.clone(),
-                            path:  path.clone(),
-                            scope: scope.clone(),
+                        let destructured = match destructure_to {
+                            Some(destructure_to) => self.destructure(
+                                argument.clone(),
+                                destructure_to,
+                                *ellipsis,
+                                path,
+                                &scope,
+                                location,
+                            ),
+                            None => Ok(()),
                         };
-                        let value = if argument_index + 1 == arguments.len() {
-                            value
-                        } else {
-                            Value::FunctionApplication {
-                                argument_index: argument_index + 1,
-                                arguments,
-                                function: Rc::new(value),
-                                location: location.clone(),
+
+                        let value = destructured.and_then(|()| {
+                            if let Some(bind_to) = bind_to {
+                                scope.bind(bind_to.clone(), argument);
                             }
-                        };
-                        let value = Rc::new(value);
-                        let value = self.advance_monotonically(value)?;
 
-                        Ok(value)
+                            let value = Value::thunk(
+                                implementation.clone(),
+                                path.clone(),
+                                &scope,
+                            );
+                            let value = if argument_index + 1 == arguments.len()
+                            {
+                                value
+                            } else {
+                                Value::FunctionApplication {
+                                    argument_index: argument_index + 1,
+                                    arguments,
+                                    function: Rc::new(value),
+                                    location: location.clone(),
+                                }
+                            };
+                            let value = Rc::new(value);
+                            self.advance_monotonically(value)
+                        });
+
+                        self.remove_stack_frame();
+                        if call {
+                            self.depth -= 1;
+                        }
+
+                        value
                     }
                     _ => Err(Error::Interpreter {
                         description: format!(
@@ -122,32 +376,53 @@ impl Runtime {
                 }
             }
 
+            Value::HasProperty { attributes, expression, path, scope } => {
+                let selected =
+                    self.select(expression.clone(), attributes, path, scope)?;
+
+                Ok(Rc::new(Value::Boolean(selected.is_ok())))
+            }
+
+            Value::PropertyAccess {
+                attributes,
+                default,
+                expression,
+                path,
+                scope,
+            } => {
+                let selected =
+                    self.select(expression.clone(), attributes, path, scope)?;
+
+                match (selected, default) {
+                    (Ok(value), _) => Ok(value),
+                    (Err(_), Some(default)) => Ok(default.clone()),
+                    (Err((description, location)), None) => {
+                        Err(Error::Interpreter {
+                            description,
+                            location,
+                            stack: self.stack.clone(),
+                        })
+                    }
+                }
+            }
+
             Value::Variable { identifier, location, scope } => {
                 match scope.lookup(identifier) {
                     Some(value) => Ok(value),
-                    None => match identifier.as_str() {
-                        "built-in +" => {
-                            Ok(Rc::new(Value::FunctionApplication {
-                                argument_index: 0,
-                                arguments:      Vec::new(),
-                                function:       Rc::new(
-                                    Value::BuiltInFunction {
-                                        expected_arguments: 2,
-                                        identifier:         identifier.clone(),
-                                    },
+                    None => match self.globals.get(identifier) {
+                        Some(value) => Ok(value.clone()),
+                        None => match self
+                            .lookup_with(identifier, scope, location)?
+                        {
+                            Some(value) => Ok(value),
+                            None => Err(Error::Interpreter {
+                                description: format!(
+                                    "undefined variable {identifier:?}"
                                 ),
-                                location:       location.clone(),
-                            }))
-                        }
-                        "false" => Ok(Rc::new(Value::Boolean(false))),
-                        "true" => Ok(Rc::new(Value::Boolean(true))),
-                        _ => Err(Error::Interpreter {
-                            description: format!(
-                                "undefined variable {identifier:?}"
-                            ),
-                            location:    location.clone(),
-                            stack:       self.stack.clone(),
-                        }),
+                                location:    location.clone(),
+                                stack:       self.stack.clone(),
+                            }),
+                        },
                     },
                 }
             }
@@ -158,26 +433,293 @@ impl Runtime {
         value
     }
 
+    /// Select the attribute path `attributes` from `value`.
+    ///
+    /// The inner result explains why the selection is not possible,
+    /// so that `or` and `?` can recover from it.
+    fn select(
+        &mut self,
+        mut value: Rc<Value>,
+        attributes: &[(AttributeName, Position)],
+        path: &Rc<String>,
+        scope: &Scope,
+    ) -> Result<Result<Rc<Value>, (String, Location)>, Error> {
+        for (attribute, position) in attributes {
+            let location = Location::at(path.clone(), position);
+
+            let attribute = match attribute {
+                AttributeName::Static(attribute) => attribute.clone(),
+                AttributeName::Dynamic(ast) => {
+                    let name =
+                        Rc::new(Value::thunk(ast.clone(), path.clone(), scope));
+
+                    match self.attribute_name(name, &location)? {
+                        Some(attribute) => attribute,
+                        None => {
+                            return Err(Error::Interpreter {
+                                description: "selecting attribute null is not \
+                                              possible"
+                                    .to_string(),
+                                location,
+                                stack: self.stack.clone(),
+                            });
+                        }
+                    }
+                }
+            };
+
+            value = self.advance_monotonically(value)?;
+
+            let description = match &*value {
+                Value::AttributeSet(attributes) => {
+                    match attributes.get(&attribute) {
+                        Some(attribute_value) => {
+                            value = attribute_value.clone();
+                            continue;
+                        }
+                        None => format!(
+                            "attribute {attribute:?} missing, available \
+                             attributes are: {:?}",
+                            attributes.keys().collect::<Vec<_>>(),
+                        ),
+                    }
+                }
+                _ => format!(
+                    "selecting attribute {attribute:?} from a {:?} is not \
+                     possible",
+                    value.kind(),
+                ),
+            };
+
+            return Ok(Err((description, location)));
+        }
+
+        Ok(Ok(value))
+    }
+
+    /// Force the name of a dynamic attribute like `${name}`,
+    /// which is `None` when it evaluates to `null`.
+    fn attribute_name(
+        &mut self,
+        name: Rc<Value>,
+        location: &Location,
+    ) -> Result<Option<String>, Error> {
+        let location = operand_location(&name, location);
+
+        let name = self.advance_monotonically(name)?;
+
+        match &*name {
+            Value::Null => Ok(None),
+            Value::String { value, .. } => Ok(Some(value.clone())),
+            _ => Err(Error::Interpreter {
+                description: format!(
+                    "using a {:?} as an attribute name is not possible, \
+                     expected a String",
+                    name.kind(),
+                ),
+                location,
+                stack: self.stack.clone(),
+            }),
+        }
+    }
+
+    /// Look up a variable in the enclosing `with` expressions,
+    /// innermost first, forcing them only as needed.
+    fn lookup_with(
+        &mut self,
+        identifier: &str,
+        scope: &Scope,
+        location: &Location,
+    ) -> Result<Option<Rc<Value>>, Error> {
+        for expression in scope.with_expressions() {
+            let expression = self.advance_monotonically(expression)?;
+
+            match &*expression {
+                Value::AttributeSet(attributes) => {
+                    if let Some(value) = attributes.get(identifier) {
+                        return Ok(Some(value.clone()));
+                    }
+                }
+                _ => {
+                    return Err(Error::Interpreter {
+                        description: format!(
+                            "using a {:?} in a with expression is not \
+                             possible, expected an AttributeSet",
+                            expression.kind(),
+                        ),
+                        location:    location.clone(),
+                        stack:       self.stack.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
     pub(crate) fn advance_monotonically(
         &mut self,
         mut value: Rc<Value>,
     ) -> Result<Rc<Value>, Error> {
         let mut old_value;
-
         loop {
             old_value = value;
 
-            value = self.advance(&old_value)?;
+            match self.advance(&old_value) {
+                Ok(new_value) if Rc::ptr_eq(&new_value, &old_value) => {
+                    break Ok(new_value);
+                }
+                Ok(new_value) => value = new_value,
+                Err(error) => break Err(error),
+            }
+        }
+    }
+
+    pub(crate) fn advance_deeply(
+        &mut self,
+        value: Rc<Value>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let value = self.advance_monotonically(value)?;
+
+        match &*value {
+            Value::AttributeSet(attributes) => {
+                let mut attributes_advanced = BTreeMap::new();
+
+                for (attribute, attribute_value) in attributes {
+                    attributes_advanced.insert(
+                        attribute.clone(),
+                        self.nested(location, |runtime| {
+                            runtime.advance_deeply(
+                                attribute_value.clone(),
+                                location,
+                            )
+                        })?,
+                    );
+                }
+
+                Ok(Rc::new(Value::AttributeSet(attributes_advanced)))
+            }
+            Value::List(elements) => {
+                let mut elements_advanced = Vec::with_capacity(elements.len());
+
+                for element in elements {
+                    elements_advanced.push(self.nested(
+                        location,
+                        |runtime| {
+                            runtime.advance_deeply(element.clone(), location)
+                        },
+                    )?);
+                }
 
-            if Rc::ptr_eq(&value, &old_value) {
-                break;
+                Ok(Rc::new(Value::List(elements_advanced)))
             }
+            _ => Ok(value),
         }
+    }
 
-        Ok(value)
+    /// Run `nested` one level deeper on the native stack,
+    /// failing instead if that is too deep.
+    pub(crate) fn nested<T>(
+        &mut self,
+        location: &Location,
+        nested: impl FnOnce(&mut Runtime) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self.check_depth(location)?;
+        self.depth += 1;
+        let result = nested(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn check_depth(&self, location: &Location) -> Result<(), Error> {
+        if self.depth >= MAX_DEPTH {
+            Err(Error::Interpreter {
+                description: "stack overflow (possible infinite recursion)"
+                    .to_string(),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Bind the attributes of the argument to the formals of a function
+    /// like `{ a, b ? 2, ... }: ...`.
+    ///
+    /// Defaults are thunks in the scope of the function,
+    /// so they can refer to the other formals.
+    fn destructure(
+        &mut self,
+        argument: Rc<Value>,
+        destructure_to: &[FunctionArgument],
+        ellipsis: bool,
+        path: &Rc<String>,
+        scope: &Scope,
+        location: &Location,
+    ) -> Result<(), Error> {
+        let argument = self.advance_monotonically(argument)?;
+
+        let attributes = match &*argument {
+            Value::AttributeSet(attributes) => attributes,
+            _ => {
+                return Err(Error::Interpreter {
+                    description: format!(
+                        "calling a Function that destructures its argument \
+                         with a {:?} is not possible, expected an AttributeSet",
+                        argument.kind(),
+                    ),
+                    location:    location.clone(),
+                    stack:       self.stack.clone(),
+                });
+            }
+        };
+
+        for FunctionArgument { identifier, default } in destructure_to {
+            let value = match (attributes.get(identifier), default) {
+                (Some(value), _) => value.clone(),
+                (None, Some(default)) => {
+                    Rc::new(Value::thunk(*default.clone(), path.clone(), scope))
+                }
+                (None, None) => {
+                    return Err(Error::Interpreter {
+                        description: format!(
+                            "function called without required argument \
+                             {identifier:?}",
+                        ),
+                        location:    location.clone(),
+                        stack:       self.stack.clone(),
+                    });
+                }
+            };
+
+            scope.bind(identifier.clone(), value);
+        }
+
+        if !ellipsis {
+            for attribute in attributes.keys() {
+                if !destructure_to
+                    .iter()
+                    .any(|argument| &argument.identifier == attribute)
+                {
+                    return Err(Error::Interpreter {
+                        description: format!(
+                            "function called with unexpected argument \
+                             {attribute:?}",
+                        ),
+                        location:    location.clone(),
+                        stack:       self.stack.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
     }
 
-    fn built_in_addition(
+    pub(crate) fn built_in_addition(
         &mut self,
         mut args: Vec<Rc<Value>>,
         location: &Location,
@@ -188,6 +730,10 @@ impl Runtime {
         let lhs = self.advance_monotonically(lhs)?;
         let rhs = self.advance_monotonically(rhs)?;
 
+        if let Some((lhs_value, rhs_value)) = floats(&lhs, &rhs) {
+            return Ok(Rc::new(Value::Float(Float(lhs_value + rhs_value))));
+        }
+
         match (&*lhs, &*rhs) {
             (Value::Int(lhs_value), Value::Int(rhs_value)) => {
                 match lhs_value.checked_add(*rhs_value) {
@@ -202,6 +748,53 @@ impl Runtime {
                     }),
                 }
             }
+            (
+                Value::String { context: lhs_context, value: lhs_value },
+                Value::String { context: rhs_context, value: rhs_value },
+            ) => Ok(Rc::new(Value::String {
+                context: lhs_context.union(rhs_context).cloned().collect(),
+                value:   format!("{lhs_value}{rhs_value}"),
+            })),
+            (Value::Path(lhs_value), Value::Path(rhs_value)) => {
+                Ok(Rc::new(Value::Path(canonicalize_path(&format!(
+                    "{lhs_value}{rhs_value}"
+                )))))
+            }
+            (
+                Value::Path(lhs_value),
+                Value::String { context, value: rhs_value },
+            ) => {
+                if context.is_empty() {
+                    Ok(Rc::new(Value::Path(canonicalize_path(&format!(
+                        "{lhs_value}{rhs_value}"
+                    )))))
+                } else {
+                    Err(Error::Interpreter {
+                        description: format!(
+                            "a string that refers to a store path cannot be \
+                             appended to a path, while adding {lhs_value:?} \
+                             and {rhs_value:?}"
+                        ),
+                        location:    location.clone(),
+                        stack:       self.stack.clone(),
+                    })
+                }
+            }
+            (
+                Value::String { context: lhs_context, value: lhs_value },
+                Value::Path(_),
+            ) => {
+                let (rhs_value, rhs_context) = self.coerce_to_string(
+                    rhs.clone(),
+                    Coercion::Interpolation,
+                    location,
+                )?;
+
+                Ok(Rc::new(Value::String {
+                    context: lhs_context.union(&rhs_context).cloned().collect(),
+                    value:   format!("{lhs_value}{rhs_value}"),
+                }))
+            }
             _ => Err(Error::Interpreter {
                 description: format!(
                     "built-in + is not implemented for operands of type {:?} \
@@ -214,4 +807,968 @@ impl Runtime {
             }),
         }
     }
+
+    pub(crate) fn built_in_add(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        self.arithmetic(
+            args,
+            location,
+            "add",
+            "adding",
+            i64::checked_add,
+            |lhs, rhs| lhs + rhs,
+        )
+    }
+
+    pub(crate) fn built_in_subtraction(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        self.arithmetic(
+            args,
+            location,
+            "-",
+            "subtracting",
+            i64::checked_sub,
+            |lhs, rhs| lhs - rhs,
+        )
+    }
+
+    pub(crate) fn built_in_multiplication(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        self.arithmetic(
+            args,
+            location,
+            "*",
+            "multiplying",
+            i64::checked_mul,
+            |lhs, rhs| lhs * rhs,
+        )
+    }
+
+    pub(crate) fn built_in_division(
+        &mut self,
+        args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = self.advance_monotonically(args[1].clone())?;
+
+        if let Value::Int(0) | Value::Float(Float(0.0)) = &*rhs {
+            return Err(Error::Interpreter {
+                description: "division by zero".to_string(),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            });
+        }
+
+        self.arithmetic(
+            args,
+            location,
+            "/",
+            "dividing",
+            i64::checked_div,
+            |lhs, rhs| lhs / rhs,
+        )
+    }
+
+    /// Shared implementation of the numeric operators other than `+`,
+    /// which also works on strings and paths.
+    ///
+    /// Integers are promoted to floats when the other operand is a float.
+    fn arithmetic(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+        operator: &str,
+        action: &str,
+        operation: fn(i64, i64) -> Option<i64>,
+        float_operation: fn(f64, f64) -> f64,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let lhs = self.advance_monotonically(lhs)?;
+        let rhs = self.advance_monotonically(rhs)?;
+
+        if let Some((lhs_value, rhs_value)) = floats(&lhs, &rhs) {
+            return Ok(Rc::new(Value::Float(Float(float_operation(
+                lhs_value, rhs_value,
+            )))));
+        }
+
+        match (&*lhs, &*rhs) {
+            (Value::Int(lhs_value), Value::Int(rhs_value)) => {
+                match operation(*lhs_value, *rhs_value) {
+                    Some(value) => Ok(Rc::new(Value::Int(value))),
+                    None => Err(Error::Interpreter {
+                        description: format!(
+                            "integer overflow while {action} {lhs_value} and \
+                             {rhs_value}"
+                        ),
+                        location:    location.clone(),
+                        stack:       self.stack.clone(),
+                    }),
+                }
+            }
+            _ => Err(Error::Interpreter {
+                description: format!(
+                    "built-in {operator} is not implemented for operands of \
+                     type {:?} and {:?}",
+                    lhs.kind(),
+                    rhs.kind(),
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn built_in_negation(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let value = self.advance_monotonically(args.remove(0))?;
+
+        match &*value {
+            Value::Float(Float(value)) => {
+                Ok(Rc::new(Value::Float(Float(-value))))
+            }
+            Value::Int(value) => match value.checked_neg() {
+                Some(value) => Ok(Rc::new(Value::Int(value))),
+                None => Err(Error::Interpreter {
+                    description: format!(
+                        "integer overflow while negating {value}"
+                    ),
+                    location:    location.clone(),
+                    stack:       self.stack.clone(),
+                }),
+            },
+            _ => Err(Error::Interpreter {
+                description: format!(
+                    "built-in unary - is not implemented for an operand of \
+                     type {:?}",
+                    value.kind(),
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn built_in_equal_to(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let equal = self.equal(lhs, rhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(equal)))
+    }
+
+    pub(crate) fn built_in_not_equal_to(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let equal = self.equal(lhs, rhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(!equal)))
+    }
+
+    /// Deep structural equality, forcing lists and attribute sets as needed.
+    ///
+    /// String contexts are ignored and functions are never equal,
+    /// just like in Nix.
+    pub(crate) fn equal(
+        &mut self,
+        lhs: Rc<Value>,
+        rhs: Rc<Value>,
+        location: &Location,
+    ) -> Result<bool, Error> {
+        let lhs = self.advance_monotonically(lhs)?;
+        let rhs = self.advance_monotonically(rhs)?;
+
+        if let Some((lhs_value, rhs_value)) = floats(&lhs, &rhs) {
+            return Ok(lhs_value == rhs_value);
+        }
+
+        match (&*lhs, &*rhs) {
+            (
+                Value::AttributeSet(lhs_value),
+                Value::AttributeSet(rhs_value),
+            ) => {
+                if lhs_value.len() != rhs_value.len() {
+                    return Ok(false);
+                }
+
+                for ((lhs_name, lhs_value), (rhs_name, rhs_value)) in
+                    lhs_value.iter().zip(rhs_value)
+                {
+                    if lhs_name != rhs_name
+                        || !self.nested(location, |runtime| {
+                            runtime.equal(
+                                lhs_value.clone(),
+                                rhs_value.clone(),
+                                location,
+                            )
+                        })?
+                    {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            }
+            (Value::Boolean(lhs_value), Value::Boolean(rhs_value)) => {
+                Ok(lhs_value == rhs_value)
+            }
+            (Value::Int(lhs_value), Value::Int(rhs_value)) => {
+                Ok(lhs_value == rhs_value)
+            }
+            (Value::List(lhs_value), Value::List(rhs_value)) => {
+                if lhs_value.len() != rhs_value.len() {
+                    return Ok(false);
+                }
+
+                for (lhs_value, rhs_value) in lhs_value.iter().zip(rhs_value) {
+                    if !self.nested(location, |runtime| {
+                        runtime.equal(
+                            lhs_value.clone(),
+                            rhs_value.clone(),
+                            location,
+                        )
+                    })? {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            }
+            (Value::Null, Value::Null) => Ok(true),
+            (Value::Path(lhs_value), Value::Path(rhs_value)) => {
+                Ok(lhs_value == rhs_value)
+            }
+            (
+                Value::String { value: lhs_value, .. },
+                Value::String { value: rhs_value, .. },
+            ) => Ok(lhs_value == rhs_value),
+            _ => Ok(false),
+        }
+    }
+
+    pub(crate) fn built_in_less_than(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let less_than = self.less_than(lhs, rhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(less_than)))
+    }
+
+    pub(crate) fn built_in_less_than_or_equal_to(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let greater_than = self.less_than(rhs, lhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(!greater_than)))
+    }
+
+    pub(crate) fn built_in_greater_than(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let greater_than = self.less_than(rhs, lhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(greater_than)))
+    }
+
+    pub(crate) fn built_in_greater_than_or_equal_to(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let less_than = self.less_than(lhs, rhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(!less_than)))
+    }
+
+    /// Ordering used by `<`, `<=`, `>` and `>=`.
+    ///
+    /// Lists compare lexicographically,
+    /// the first pair of elements that are not equal decides.
+    pub(crate) fn less_than(
+        &mut self,
+        lhs: Rc<Value>,
+        rhs: Rc<Value>,
+        location: &Location,
+    ) -> Result<bool, Error> {
+        let lhs = self.advance_monotonically(lhs)?;
+        let rhs = self.advance_monotonically(rhs)?;
+
+        if let Some((lhs_value, rhs_value)) = floats(&lhs, &rhs) {
+            return Ok(lhs_value < rhs_value);
+        }
+
+        match (&*lhs, &*rhs) {
+            (Value::Int(lhs_value), Value::Int(rhs_value)) => {
+                Ok(lhs_value < rhs_value)
+            }
+            (Value::List(lhs_value), Value::List(rhs_value)) => {
+                for (lhs_value, rhs_value) in lhs_value.iter().zip(rhs_value) {
+                    if !self.equal(
+                        lhs_value.clone(),
+                        rhs_value.clone(),
+                        location,
+                    )? {
+                        return self.nested(location, |runtime| {
+                            runtime.less_than(
+                                lhs_value.clone(),
+                                rhs_value.clone(),
+                                location,
+                            )
+                        });
+                    }
+                }
+
+                Ok(lhs_value.len() < rhs_value.len())
+            }
+            (Value::Path(lhs_value), Value::Path(rhs_value)) => {
+                Ok(lhs_value < rhs_value)
+            }
+            (
+                Value::String { value: lhs_value, .. },
+                Value::String { value: rhs_value, .. },
+            ) => Ok(lhs_value < rhs_value),
+            _ => Err(Error::Interpreter {
+                description: format!(
+                    "comparing a {:?} with a {:?} is not possible",
+                    lhs.kind(),
+                    rhs.kind(),
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn built_in_and(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let value =
+            self.boolean(lhs, location)? && self.boolean(rhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(value)))
+    }
+
+    pub(crate) fn built_in_or(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let value =
+            self.boolean(lhs, location)? || self.boolean(rhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(value)))
+    }
+
+    pub(crate) fn built_in_implication(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let value =
+            !self.boolean(lhs, location)? || self.boolean(rhs, location)?;
+
+        Ok(Rc::new(Value::Boolean(value)))
+    }
+
+    pub(crate) fn built_in_not(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let value = self.boolean(args.remove(0), location)?;
+
+        Ok(Rc::new(Value::Boolean(!value)))
+    }
+
+    pub(crate) fn built_in_if(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let else_ = args.remove(2);
+        let then = args.remove(1);
+        let predicate = args.remove(0);
+
+        if self.boolean(predicate, location)? {
+            Ok(then)
+        } else {
+            Ok(else_)
+        }
+    }
+
+    pub(crate) fn built_in_assert(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let target = args.remove(1);
+        let expression = args.remove(0);
+
+        if self.boolean(expression, location)? {
+            Ok(target)
+        } else {
+            Err(Error::Assertion {
+                description: "assertion failed".to_string(),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            })
+        }
+    }
+
+    /// Force an operand that must be a `Value::Boolean`.
+    ///
+    /// Errors point at the operand when its location is known,
+    /// and at `location` otherwise.
+    pub(crate) fn boolean(
+        &mut self,
+        value: Rc<Value>,
+        location: &Location,
+    ) -> Result<bool, Error> {
+        let location = operand_location(&value, location);
+        let value = self.advance_monotonically(value)?;
+
+        match &*value {
+            Value::Boolean(value) => Ok(*value),
+            _ => Err(self.unexpected_kind("a Boolean", &value, location)),
+        }
+    }
+
+    /// Force an operand that must be a `Value::Int`,
+    /// errors are reported like in `boolean`.
+    pub(crate) fn int(
+        &mut self,
+        value: Rc<Value>,
+        location: &Location,
+    ) -> Result<i64, Error> {
+        let location = operand_location(&value, location);
+        let value = self.advance_monotonically(value)?;
+
+        match &*value {
+            Value::Int(value) => Ok(*value),
+            _ => Err(self.unexpected_kind("an Int", &value, location)),
+        }
+    }
+
+    /// Force an operand that must be a `Value::List` and return its elements,
+    /// errors are reported like in `boolean`.
+    pub(crate) fn list(
+        &mut self,
+        value: Rc<Value>,
+        location: &Location,
+    ) -> Result<Vec<Rc<Value>>, Error> {
+        let location = operand_location(&value, location);
+        let value = self.advance_monotonically(value)?;
+
+        match &*value {
+            Value::List(elements) => Ok(elements.clone()),
+            _ => Err(self.unexpected_kind("a List", &value, location)),
+        }
+    }
+
+    /// Force an operand that must be a `Value::AttributeSet`,
+    /// errors are reported like in `boolean`.
+    pub(crate) fn attribute_set(
+        &mut self,
+        value: Rc<Value>,
+        location: &Location,
+    ) -> Result<BTreeMap<String, Rc<Value>>, Error> {
+        let location = operand_location(&value, location);
+        let value = self.advance_monotonically(value)?;
+
+        match &*value {
+            Value::AttributeSet(attributes) => Ok(attributes.clone()),
+            _ => Err(self.unexpected_kind("an AttributeSet", &value, location)),
+        }
+    }
+
+    /// Force an operand that must be a `Value::String` and return its
+    /// contents without the context, errors are reported like in `boolean`.
+    pub(crate) fn string(
+        &mut self,
+        value: Rc<Value>,
+        location: &Location,
+    ) -> Result<String, Error> {
+        let (value, _) = self.string_with_context(value, location)?;
+
+        Ok(value)
+    }
+
+    /// Like `string`, but keeping the context.
+    pub(crate) fn string_with_context(
+        &mut self,
+        value: Rc<Value>,
+        location: &Location,
+    ) -> Result<(String, BTreeSet<String>), Error> {
+        let location = operand_location(&value, location);
+        let value = self.advance_monotonically(value)?;
+
+        match &*value {
+            Value::String { context, value } => {
+                Ok((value.clone(), context.clone()))
+            }
+            _ => Err(self.unexpected_kind("a String", &value, location)),
+        }
+    }
+
+    /// Force the argument number `number` of a built-in with `force`,
+    /// so that an argument of an unexpected kind is reported by its number.
+    pub(crate) fn argument<T>(
+        &mut self,
+        number: usize,
+        value: Rc<Value>,
+        location: &Location,
+        force: fn(&mut Runtime, Rc<Value>, &Location) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let location = operand_location(&value, location);
+        let value = self.advance_monotonically(value)?;
+
+        // The value is in weak head normal form already,
+        // so the only possible error is its kind
+        force(self, value, &location).map_err(|error| match error {
+            Error::Interpreter { description, location, stack } => {
+                Error::Interpreter {
+                    description: format!("argument #{number}: {description}"),
+                    location,
+                    stack,
+                }
+            }
+            error => error,
+        })
+    }
+
+    pub(crate) fn unexpected_kind(
+        &self,
+        expected: &str,
+        value: &Value,
+        location: Location,
+    ) -> Error {
+        Error::Interpreter {
+            description: format!(
+                "{expected} was expected but found a {:?}",
+                value.kind(),
+            ),
+            location,
+            stack: self.stack.clone(),
+        }
+    }
+
+    /// Call `function` with `arguments` and force the result.
+    pub(crate) fn call(
+        &mut self,
+        function: Rc<Value>,
+        arguments: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        self.advance_monotonically(Rc::new(Value::FunctionApplication {
+            argument_index: 0,
+            arguments,
+            function,
+            location: location.clone(),
+        }))
+    }
+
+    pub(crate) fn built_in_concatenation(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let rhs = args.remove(1);
+        let lhs = args.remove(0);
+
+        let lhs = self.advance_monotonically(lhs)?;
+        let rhs = self.advance_monotonically(rhs)?;
+
+        match (&*lhs, &*rhs) {
+            (Value::List(lhs_elements), Value::List(rhs_elements)) => {
+                Ok(Rc::new(Value::List(
+                    lhs_elements.iter().chain(rhs_elements).cloned().collect(),
+                )))
+            }
+            _ => Err(Error::Interpreter {
+                description: format!(
+                    "built-in ++ is not implemented for operands of type {:?} \
+                     and {:?}",
+                    lhs.kind(),
+                    rhs.kind(),
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn built_in_dynamic_attributes(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let mut attributes = self.attribute_set(args.remove(0), location)?;
+
+        let mut names_and_values =
+            self.list(args.remove(0), location)?.into_iter();
+        while let (Some(name), Some(value)) =
+            (names_and_values.next(), names_and_values.next())
+        {
+            let name_location = operand_location(&name, location);
+
+            // Attributes named `null` are left out
+            let name = match self.attribute_name(name, location)? {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if attributes.contains_key(&name) {
+                return Err(Error::Interpreter {
+                    description: format!(
+                        "dynamic attribute {name:?} already defined"
+                    ),
+                    location:    name_location,
+                    stack:       self.stack.clone(),
+                });
+            }
+
+            attributes.insert(name, value);
+        }
+
+        Ok(Rc::new(Value::AttributeSet(attributes)))
+    }
+
+    pub(crate) fn built_in_function_args(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let function = self.advance_monotonically(args.remove(0))?;
+
+        match &*function {
+            Value::Function { destructure_to, .. } => {
+                Ok(Rc::new(Value::AttributeSet(
+                    destructure_to
+                        .iter()
+                        .flatten()
+                        .map(|FunctionArgument { identifier, default }| {
+                            (
+                                identifier.clone(),
+                                Rc::new(Value::Boolean(default.is_some())),
+                            )
+                        })
+                        .collect(),
+                )))
+            }
+            Value::BuiltInFunction { .. } => {
+                Ok(Rc::new(Value::AttributeSet(BTreeMap::new())))
+            }
+            _ => Err(Error::Interpreter {
+                description: format!(
+                    "getting the arguments of a {:?} is not possible, \
+                     expected a Function",
+                    function.kind(),
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn built_in_import(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let path = self.advance_monotonically(args.remove(0))?;
+
+        let path = match &*path {
+            Value::Path(path) => path.clone(),
+            Value::String { value, .. } if value.starts_with('/') => {
+                value.clone()
+            }
+            _ => {
+                return Err(Error::Interpreter {
+                    description: format!(
+                        "importing a {:?} is not possible, expected a Path or \
+                         a String with an absolute path",
+                        path.kind(),
+                    ),
+                    location:    location.clone(),
+                    stack:       self.stack.clone(),
+                });
+            }
+        };
+
+        let path = match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => format!("{path}/default.nix"),
+            _ => path,
+        };
+
+        let path = match std::fs::canonicalize(&path) {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(error) => {
+                return Err(Error::Interpreter {
+                    description: format!(
+                        "importing {path:?} is not possible: {error}"
+                    ),
+                    location:    location.clone(),
+                    stack:       self.stack.clone(),
+                });
+            }
+        };
+
+        if let Some(value) = self.imports.get(&path) {
+            return Ok(value.clone());
+        }
+
+        log::debug!("import {path:?}");
+        let ast = self.build_ast(&path)?;
+
+        let value =
+            Rc::new(Value::thunk(ast, Rc::new(path.clone()), &Scope::empty()));
+        self.imports.insert(path, value.clone());
+
+        Ok(value)
+    }
+
+    pub(crate) fn built_in_interpolation(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let mut context = BTreeSet::new();
+        let mut value = String::new();
+
+        for arg in self.list(args.remove(0), location)? {
+            let (arg_value, mut arg_context) =
+                self.coerce_to_string(arg, Coercion::Interpolation, location)?;
+
+            context.append(&mut arg_context);
+            value.push_str(&arg_value);
+        }
+
+        Ok(Rc::new(Value::String { context, value }))
+    }
+
+    pub(crate) fn built_in_path_interpolation(
+        &mut self,
+        mut args: Vec<Rc<Value>>,
+        location: &Location,
+    ) -> Result<Rc<Value>, Error> {
+        let mut value = String::new();
+
+        for arg in self.list(args.remove(0), location)? {
+            let (arg_value, _) =
+                self.coerce_to_string(arg, Coercion::Interpolation, location)?;
+
+            value.push_str(&arg_value);
+        }
+
+        Ok(Rc::new(Value::Path(canonicalize_path(&value))))
+    }
+
+    /// Coerce a value into a string and its context,
+    /// like Nix does when interpolating `"${value}"`
+    /// or in `toString value`, depending on the `coercion`.
+    pub(crate) fn coerce_to_string(
+        &mut self,
+        value: Rc<Value>,
+        coercion: Coercion,
+        location: &Location,
+    ) -> Result<(String, BTreeSet<String>), Error> {
+        let value = self.advance_monotonically(value)?;
+        let to_string = coercion == Coercion::ToString;
+
+        match &*value {
+            Value::String { context, value } => {
+                Ok((value.clone(), context.clone()))
+            }
+            Value::Path(path) if coercion == Coercion::Interpolation => {
+                let store_path = self.copy_path_to_store(path, location)?;
+
+                Ok((store_path.clone(), BTreeSet::from([store_path])))
+            }
+            Value::Path(path) => Ok((path.clone(), BTreeSet::new())),
+            Value::AttributeSet(attributes)
+                if attributes.contains_key("__toString") =>
+            {
+                let string = self.call(
+                    attributes["__toString"].clone(),
+                    vec![value.clone()],
+                    location,
+                )?;
+
+                self.nested(location, |runtime| {
+                    runtime.coerce_to_string(string, coercion, location)
+                })
+            }
+            Value::AttributeSet(attributes)
+                if attributes.contains_key("outPath") =>
+            {
+                self.nested(location, |runtime| {
+                    runtime.coerce_to_string(
+                        attributes["outPath"].clone(),
+                        coercion,
+                        location,
+                    )
+                })
+            }
+            Value::Boolean(true) if to_string => {
+                Ok(("1".to_string(), BTreeSet::new()))
+            }
+            Value::Boolean(false) | Value::Null if to_string => {
+                Ok((String::new(), BTreeSet::new()))
+            }
+            Value::Float(Float(value)) if to_string => {
+                Ok((format!("{value:.6}"), BTreeSet::new()))
+            }
+            Value::Int(value) if to_string => {
+                Ok((value.to_string(), BTreeSet::new()))
+            }
+            Value::List(elements) if to_string => {
+                let mut context = BTreeSet::new();
+                let mut string = String::new();
+
+                for (index, element) in elements.iter().enumerate() {
+                    let element =
+                        self.advance_monotonically(element.clone())?;
+                    let (element_string, mut element_context) =
+                        self.nested(location, |runtime| {
+                            runtime.coerce_to_string(
+                                element.clone(),
+                                coercion,
+                                location,
+                            )
+                        })?;
+
+                    context.append(&mut element_context);
+                    string.push_str(&element_string);
+
+                    // Like in Nix, there is no space after an empty list
+                    let empty_list = matches!(
+                        &*element,
+                        Value::List(elements) if elements.is_empty()
+                    );
+                    if index + 1 < elements.len() && !empty_list {
+                        string.push(' ');
+                    }
+                }
+
+                Ok((string, context))
+            }
+            _ => Err(Error::Interpreter {
+                description: format!(
+                    "cannot coerce a {:?} to a string",
+                    value.kind()
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+
+    /// Copy a path into the store and return its store path.
+    ///
+    /// There is no store implementation yet,
+    /// so like Nix in read-only mode,
+    /// the store path is computed but nothing is written to disk.
+    fn copy_path_to_store(
+        &mut self,
+        path: &str,
+        location: &Location,
+    ) -> Result<String, Error> {
+        if let Some(store_path) = self.store_paths.get(path) {
+            return Ok(store_path.clone());
+        }
+
+        match path_to_store_path(path) {
+            Ok(store_path) => {
+                log::debug!("copied {path:?} to {store_path:?}");
+                self.store_paths.insert(path.to_string(), store_path.clone());
+                Ok(store_path)
+            }
+            Err(error) => Err(Error::Interpreter {
+                description: format!(
+                    "copying {path:?} to the store is not possible: {error}"
+                ),
+                location:    location.clone(),
+                stack:       self.stack.clone(),
+            }),
+        }
+    }
+}
+
+/// Both operands as floats when they are numbers and at least one is a float.
+fn floats(lhs: &Value, rhs: &Value) -> Option<(f64, f64)> {
+    match (lhs, rhs) {
+        (Value::Float(Float(lhs)), Value::Float(Float(rhs))) => {
+            Some((*lhs, *rhs))
+        }
+        (Value::Float(Float(lhs)), Value::Int(rhs)) => {
+            Some((*lhs, *rhs as f64))
+        }
+        (Value::Int(lhs), Value::Float(Float(rhs))) => {
+            Some((*lhs as f64, *rhs))
+        }
+        _ => None,
+    }
+}
+
+/// Where an operand is in the source code,
+/// or `fallback` if it is not known.
+fn operand_location(value: &Value, fallback: &Location) -> Location {
+    match value {
+        Value::Thunk(thunk) => thunk.borrow().location(),
+        _ => None,
+    }
+    .unwrap_or_else(|| fallback.clone())
 }
@@ -15,9 +15,14 @@ pub(crate) struct Scope {
     parent:   Option<Box<Scope>>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub(crate) enum ScopeKind {
     Plain,
+    /// The scope introduced by `with expression; ...`.
+    ///
+    /// It has no bindings of its own,
+    /// its attributes are only looked up once every lexical scope missed.
+    With(Rc<Value>),
 }
 
 impl Scope {
@@ -58,6 +63,32 @@ impl Scope {
 
         None
     }
+
+    /// The expressions of the `with` scopes that enclose this one,
+    /// innermost first.
+    pub(crate) fn with_expressions(&self) -> Vec<Rc<Value>> {
+        let mut expressions = Vec::new();
+        let mut scope = Some(self);
+
+        while let Some(current_scope) = scope {
+            if let ScopeKind::With(expression) = &current_scope.kind {
+                expressions.push(expression.clone());
+            }
+
+            scope = current_scope.parent.as_deref();
+        }
+
+        expressions
+    }
+}
+
+impl std::fmt::Debug for ScopeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScopeKind::Plain => write!(f, "Plain"),
+            ScopeKind::With(_) => write!(f, "With"),
+        }
+    }
 }
 
 impl std::fmt::Debug for Scope {
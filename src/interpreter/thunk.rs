@@ -0,0 +1,47 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::rc::Rc;
+
+use nixel::ast::AST;
+
+use crate::interpreter::location::Location;
+use crate::interpreter::scope::Scope;
+use crate::interpreter::value::Value;
+
+/// The state of a `Value::Thunk`.
+///
+/// A thunk starts `Deferred` and is overwritten with its weak-head normal
+/// form the first time it is forced, so every `Rc<Value>` pointing to it
+/// (for instance from a `Scope`) shares the result (call-by-need).
+///
+/// While being forced it is `InProgress` (a black hole),
+/// so re-entering it means the value depends on itself.
+///
+/// Values built by the interpreter rather than parsed,
+/// like the elements of `map f list`, start `Suspended` instead.
+#[derive(Debug)]
+pub(crate) enum Thunk {
+    Deferred { ast: AST, path: Rc<String>, scope: Scope },
+    Suspended { location: Location, value: Rc<Value> },
+    InProgress { location: Location },
+    Forced(Rc<Value>),
+}
+
+impl Thunk {
+    /// Where the expression of the thunk is in the source code,
+    /// unknown once it has been forced.
+    pub(crate) fn location(&self) -> Option<Location> {
+        match self {
+            Thunk::Deferred { ast, path, .. } => {
+                let position = ast.position();
+
+                Some(Location::at(path.clone(), &position))
+            }
+            Thunk::InProgress { location } => Some(location.clone()),
+            Thunk::Suspended { location, .. } => Some(location.clone()),
+            Thunk::Forced(_) => None,
+        }
+    }
+}
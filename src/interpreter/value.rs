@@ -2,33 +2,42 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-only
 
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::LinkedList;
 use std::rc::Rc;
 
 use nixel::ast::BinaryOperator;
+use nixel::ast::FunctionArgument;
+use nixel::ast::StringPart;
+use nixel::ast::UnaryOperator;
 use nixel::ast::AST;
+use nixel::deps::santiago::lexer::Position;
 
 use super::location::Location;
-use super::location::LocationInFileFragment;
+use crate::interpreter::bindings::attribute_name;
+use crate::interpreter::bindings::AttributeName;
 use crate::interpreter::bindings::Bindings;
+use crate::interpreter::error::Error;
+use crate::interpreter::float::Float;
 use crate::interpreter::scope::Scope;
 use crate::interpreter::scope::ScopeKind;
+use crate::interpreter::thunk::Thunk;
 
 #[derive(Debug)]
 pub(crate) enum Value {
+    AttributeSet(BTreeMap<String, Rc<Value>>),
     Boolean(bool),
-    DeferredValue {
-        ast:   AST,
-        path:  Rc<String>,
-        scope: Scope,
-    },
+    /// A primop, by the name it is registered with.
     BuiltInFunction {
-        expected_arguments: usize,
-        identifier:         String,
+        identifier: String,
     },
+    Float(Float),
     Function {
         bind_to:        Option<String>,
-        // destructure_to: Vec<FunctionArgument>,
-        // ellipsis:       bool,
+        destructure_to: Option<Vec<FunctionArgument>>,
+        ellipsis:       bool,
         implementation: AST,
         path:           Rc<String>,
         scope:          Scope,
@@ -39,7 +48,28 @@ pub(crate) enum Value {
         function:       Rc<Value>,
         location:       Location,
     },
+    HasProperty {
+        attributes: Vec<(AttributeName, Position)>,
+        expression: Rc<Value>,
+        path:       Rc<String>,
+        scope:      Scope,
+    },
     Int(i64),
+    List(Vec<Rc<Value>>),
+    Null,
+    Path(String),
+    PropertyAccess {
+        attributes: Vec<(AttributeName, Position)>,
+        default:    Option<Rc<Value>>,
+        expression: Rc<Value>,
+        path:       Rc<String>,
+        scope:      Scope,
+    },
+    String {
+        context: BTreeSet<String>,
+        value:   String,
+    },
+    Thunk(RefCell<Thunk>),
     Variable {
         identifier: String,
         location:   Location,
@@ -48,8 +78,12 @@ pub(crate) enum Value {
 }
 
 impl Value {
-    pub(crate) fn from_ast(path: Rc<String>, ast: AST, scope: &Scope) -> Value {
-        match ast {
+    pub(crate) fn from_ast(
+        path: Rc<String>,
+        ast: AST,
+        scope: &Scope,
+    ) -> Result<Value, Error> {
+        let value = match ast {
             AST::BinaryOperation { operands, operator, position } => {
                 let identifier = match operator {
                     BinaryOperator::Addition => "built-in +",
@@ -69,38 +103,40 @@ impl Value {
                     BinaryOperator::Update => "built-in //",
                 };
 
-                Value::FunctionApplication {
-                    argument_index: 0,
-                    arguments:      operands
-                        .into_iter()
-                        .map(|ast| {
-                            Rc::new(Value::DeferredValue {
-                                ast,
-                                path: path.clone(),
-                                scope: scope.clone(),
-                            })
-                        })
-                        .collect(),
+                Value::built_in_application(
+                    identifier, operands, path, position, scope,
+                )
+            }
 
-                    function: Rc::new(Value::BuiltInFunction {
-                        identifier:         identifier.to_string(),
-                        expected_arguments: 2,
-                    }),
-                    location: Location::InFileFragment(
-                        LocationInFileFragment {
-                            column: position.column,
-                            line: position.line,
-                            path,
-                        },
-                    ),
-                }
+            AST::Assert { expression, target, position } => {
+                Value::built_in_application(
+                    "built-in assert",
+                    [*expression, *target],
+                    path,
+                    position,
+                    scope,
+                )
             }
 
-            AST::Function { argument, definition, .. } => {
+            AST::Float { value, .. } => Value::Float(Float(value)),
+
+            AST::Function { argument, arguments, definition, .. } => {
+                // The parser represents `x: ...` and `x@{ }: ...` the same way,
+                // the former is far more common so we take it
+                let destructure_to = if argument.is_some()
+                    && arguments.arguments.is_empty()
+                    && !arguments.ellipsis
+                {
+                    None
+                } else {
+                    Some(arguments.arguments.into_iter().rev().collect())
+                };
+
                 Value::Function {
                     bind_to: argument,
+                    destructure_to,
+                    ellipsis: arguments.ellipsis,
                     implementation: *definition,
-                    // ellipsis: arguments.ellipsis,
                     path,
                     scope: scope.clone(),
                 }
@@ -114,76 +150,543 @@ impl Value {
                     arguments:      arguments
                         .into_iter()
                         .map(|ast| {
-                            Rc::new(Value::DeferredValue {
-                                ast,
-                                path: path.clone(),
-                                scope: scope.clone(),
-                            })
+                            Rc::new(Value::thunk(ast, path.clone(), scope))
                         })
                         .collect(),
                     function:       Rc::new(Value::from_ast(
                         path.clone(),
                         *function,
                         scope,
+                    )?),
+                    location:       Location::at(path, &position),
+                }
+            }
+
+            AST::HasProperty { expression, attribute_path, position } => {
+                Value::HasProperty {
+                    attributes: attribute_path
+                        .attributes
+                        .into_iter()
+                        .map(|attribute| attribute_name(attribute, &position))
+                        .collect(),
+                    expression: Rc::new(Value::thunk(
+                        *expression,
+                        path.clone(),
+                        scope,
                     )),
-                    location:       Location::InFileFragment(
-                        LocationInFileFragment {
-                            column: position.column,
-                            line: position.line,
-                            path,
-                        },
-                    ),
+                    path,
+                    scope: scope.clone(),
                 }
             }
 
+            AST::IfThenElse { predicate, then, else_, position } => {
+                Value::built_in_application(
+                    "built-in if",
+                    [*predicate, *then, *else_],
+                    path,
+                    position,
+                    scope,
+                )
+            }
+
             AST::Int { value, .. } => Value::Int(value),
 
             AST::LetIn { bindings, target, position: _ } => {
-                let bindings = Bindings::new(bindings);
+                let bindings = Bindings::new(&path, bindings)?;
+
+                if let Some((name, _)) = bindings.dynamic.first() {
+                    let position = name.position();
+
+                    return Err(Error::Interpreter {
+                        description: "dynamic attributes are not allowed in \
+                                      let"
+                        .to_string(),
+                        location:    Location::at(path, &position),
+                        stack:       LinkedList::new(),
+                    });
+                }
 
                 let scope_with_bindings = scope.derive(ScopeKind::Plain);
+                let scope_of_inherits = scope_of_inherits(
+                    bindings.inherit_from,
+                    &path,
+                    scope,
+                    &scope_with_bindings,
+                );
 
                 for (binding_attribute, binding) in bindings.bindings {
                     scope_with_bindings.bind(
                         binding_attribute,
-                        Rc::new(Value::DeferredValue {
-                            ast:   binding.ast,
-                            path:  path.clone(),
-                            scope: if binding.inherited {
-                                scope.clone()
+                        Rc::new(Value::thunk(
+                            binding.ast,
+                            path.clone(),
+                            if binding.inherited {
+                                &scope_of_inherits
                             } else {
-                                scope_with_bindings.clone()
+                                &scope_with_bindings
                             },
-                        }),
+                        )),
+                    )
+                }
+
+                Value::from_ast(path, *target, &scope_with_bindings)?
+            }
+
+            AST::List { elements, .. } => Value::List(
+                elements
+                    .into_iter()
+                    .map(|ast| Rc::new(Value::thunk(ast, path.clone(), scope)))
+                    .collect(),
+            ),
+
+            AST::Map { bindings, recursive, position } => {
+                let bindings = Bindings::new(&path, bindings)?;
+
+                let scope_with_bindings = if recursive {
+                    scope.derive(ScopeKind::Plain)
+                } else {
+                    scope.clone()
+                };
+                let scope_of_inherits = scope_of_inherits(
+                    bindings.inherit_from,
+                    &path,
+                    scope,
+                    &scope_with_bindings,
+                );
+
+                let attribute_set = Value::AttributeSet(
+                    bindings
+                        .bindings
+                        .into_iter()
+                        .map(|(binding_attribute, binding)| {
+                            let value = Rc::new(Value::thunk(
+                                binding.ast,
+                                path.clone(),
+                                if binding.inherited {
+                                    &scope_of_inherits
+                                } else {
+                                    &scope_with_bindings
+                                },
+                            ));
+
+                            if recursive {
+                                scope_with_bindings.bind(
+                                    binding_attribute.clone(),
+                                    value.clone(),
+                                );
+                            }
+
+                            (binding_attribute, value)
+                        })
+                        .collect(),
+                );
+
+                if bindings.dynamic.is_empty() {
+                    attribute_set
+                } else {
+                    // The names of dynamic attributes are evaluated
+                    // when the attribute set is forced
+                    let mut names_and_values = Vec::new();
+
+                    for (name, binding) in bindings.dynamic {
+                        names_and_values.push(Rc::new(Value::thunk(
+                            name,
+                            path.clone(),
+                            &scope_with_bindings,
+                        )));
+                        names_and_values.push(Rc::new(Value::thunk(
+                            binding.ast,
+                            path.clone(),
+                            &scope_with_bindings,
+                        )));
+                    }
+
+                    Value::built_in_call(
+                        "built-in dynamic attributes",
+                        vec![
+                            Rc::new(attribute_set),
+                            Rc::new(Value::List(names_and_values)),
+                        ],
+                        path,
+                        position,
                     )
                 }
+            }
+
+            AST::Path { mut parts, position } => {
+                if let Some(StringPart::Raw { content }) = parts.front_mut() {
+                    *content = absolute_path(&path, content);
+                }
+
+                match parts.front() {
+                    Some(StringPart::Raw { content }) if parts.len() == 1 => {
+                        Value::Path(canonicalize_path(content))
+                    }
+                    _ => Value::interpolation(
+                        "built-in path interpolation",
+                        parts,
+                        path,
+                        position,
+                        scope,
+                    ),
+                }
+            }
 
-                Value::from_ast(path, *target, &scope_with_bindings)
+            AST::PropertyAccess { attribute_path, default, expression } => {
+                let position = expression.position();
+
+                Value::PropertyAccess {
+                    attributes: attribute_path
+                        .attributes
+                        .into_iter()
+                        .map(|attribute| attribute_name(attribute, &position))
+                        .collect(),
+                    default: default.map(|default| {
+                        Rc::new(Value::thunk(*default, path.clone(), scope))
+                    }),
+                    expression: Rc::new(Value::from_ast(
+                        path.clone(),
+                        *expression,
+                        scope,
+                    )?),
+                    path,
+                    scope: scope.clone(),
+                }
+            }
+
+            AST::String { parts, position } => {
+                if parts
+                    .iter()
+                    .all(|part| matches!(part, StringPart::Raw { .. }))
+                {
+                    return Ok(Value::String {
+                        context: BTreeSet::new(),
+                        value:   parts
+                            .into_iter()
+                            .map(|part| match part {
+                                StringPart::Raw { content } => content,
+                                StringPart::Expression { .. } => unreachable!(),
+                            })
+                            .collect(),
+                    });
+                }
+
+                Value::interpolation(
+                    "built-in interpolation",
+                    parts,
+                    path,
+                    position,
+                    scope,
+                )
             }
 
             AST::Variable { identifier, position } => Value::Variable {
                 identifier,
-                location: Location::InFileFragment(LocationInFileFragment {
-                    column: position.column,
-                    line: position.line,
-                    path,
-                }),
+                location: Location::at(path, &position),
                 scope: scope.clone(),
             },
 
+            AST::UnaryOperation { operator, operand, position } => {
+                let identifier = match operator {
+                    UnaryOperator::Negate => "built-in unary -",
+                    UnaryOperator::Not => "built-in !",
+                };
+
+                Value::built_in_application(
+                    identifier,
+                    [*operand],
+                    path,
+                    position,
+                    scope,
+                )
+            }
+
+            AST::With { expression, target, .. } => {
+                let expression =
+                    Rc::new(Value::thunk(*expression, path.clone(), scope));
+
+                Value::from_ast(
+                    path,
+                    *target,
+                    &scope.derive(ScopeKind::With(expression)),
+                )?
+            }
+
             ast => todo!("Value::from_ast: {:#?}", ast),
+        };
+
+        Ok(value)
+    }
+
+    /// Apply a built-in to the operands of an expression,
+    /// each of them deferred so that the built-in decides what to force.
+    fn built_in_application(
+        identifier: &str,
+        operands: impl IntoIterator<Item = AST>,
+        path: Rc<String>,
+        position: Position,
+        scope: &Scope,
+    ) -> Value {
+        let arguments = operands
+            .into_iter()
+            .map(|ast| Rc::new(Value::thunk(ast, path.clone(), scope)))
+            .collect();
+
+        Value::built_in_call(identifier, arguments, path, position)
+    }
+
+    /// Apply a built-in, whose arity is the one in the registry,
+    /// to `arguments`.
+    fn built_in_call(
+        identifier: &str,
+        arguments: Vec<Rc<Value>>,
+        path: Rc<String>,
+        position: Position,
+    ) -> Value {
+        Value::FunctionApplication {
+            argument_index: 0,
+            function: Rc::new(Value::BuiltInFunction {
+                identifier: identifier.to_string(),
+            }),
+            arguments,
+            location: Location::at(path, &position),
         }
     }
 
+    fn interpolation(
+        identifier: &str,
+        parts: LinkedList<StringPart>,
+        path: Rc<String>,
+        position: Position,
+        scope: &Scope,
+    ) -> Value {
+        let parts = parts
+            .into_iter()
+            .map(|part| match part {
+                StringPart::Raw { content } => Rc::new(Value::String {
+                    context: BTreeSet::new(),
+                    value:   content,
+                }),
+                StringPart::Expression { expression } => {
+                    Rc::new(Value::thunk(*expression, path.clone(), scope))
+                }
+            })
+            .collect();
+
+        Value::built_in_call(
+            identifier,
+            vec![Rc::new(Value::List(parts))],
+            path,
+            position,
+        )
+    }
+
+    pub(crate) fn thunk(ast: AST, path: Rc<String>, scope: &Scope) -> Value {
+        Value::Thunk(RefCell::new(Thunk::Deferred {
+            ast,
+            path,
+            scope: scope.clone(),
+        }))
+    }
+
+    /// A deferred call of `function` with `arguments`,
+    /// forced at most once like any other thunk.
+    pub(crate) fn suspended_application(
+        function: Rc<Value>,
+        arguments: Vec<Rc<Value>>,
+        location: Location,
+    ) -> Value {
+        Value::Thunk(RefCell::new(Thunk::Suspended {
+            location: location.clone(),
+            value:    Rc::new(Value::FunctionApplication {
+                argument_index: 0,
+                arguments,
+                function,
+                location,
+            }),
+        }))
+    }
+
     pub(crate) fn kind(&self) -> &str {
         match &self {
+            Value::AttributeSet { .. } => "AttributeSet",
             Value::Boolean { .. } => "Boolean",
             Value::BuiltInFunction { .. } => "BuiltInFunction",
-            Value::DeferredValue { .. } => "DeferredValue",
+            Value::Float { .. } => "Float",
             Value::Function { .. } => "Function",
             Value::FunctionApplication { .. } => "FunctionApplication",
+            Value::HasProperty { .. } => "HasProperty",
             Value::Int { .. } => "Int",
+            Value::List { .. } => "List",
+            Value::Null => "Null",
+            Value::Path { .. } => "Path",
+            Value::PropertyAccess { .. } => "PropertyAccess",
+            Value::String { .. } => "String",
+            Value::Thunk { .. } => "Thunk",
             Value::Variable { .. } => "Variable",
         }
     }
 }
+
+/// Like Nix prints values, for instance `{ a = [ 1 "b" ]; }`.
+///
+/// What has not been forced yet is printed as `«thunk»`.
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::AttributeSet(attributes) => {
+                write!(f, "{{ ")?;
+                for (name, value) in attributes {
+                    if is_identifier(name) {
+                        write!(f, "{name}")?;
+                    } else {
+                        write_string(f, name)?;
+                    }
+                    write!(f, " = {value}; ")?;
+                }
+                write!(f, "}}")
+            }
+            Value::Boolean(value) => write!(f, "{value}"),
+            Value::BuiltInFunction { identifier } => {
+                write!(f, "«primop {identifier}»")
+            }
+            Value::Float(value) => write!(f, "{value}"),
+            Value::Function { .. } => write!(f, "«lambda»"),
+            Value::FunctionApplication { function, .. } => match &**function {
+                Value::BuiltInFunction { identifier } => {
+                    write!(f, "«partially applied primop {identifier}»")
+                }
+                _ => write!(f, "«thunk»"),
+            },
+            Value::Int(value) => write!(f, "{value}"),
+            Value::List(elements) => {
+                write!(f, "[ ")?;
+                for element in elements {
+                    write!(f, "{element} ")?;
+                }
+                write!(f, "]")
+            }
+            Value::Null => write!(f, "null"),
+            Value::Path(path) => write!(f, "{path}"),
+            Value::String { value, .. } => write_string(f, value),
+            Value::Thunk(thunk) => match &*thunk.borrow() {
+                Thunk::Forced(value) => write!(f, "{value}"),
+                _ => write!(f, "«thunk»"),
+            },
+            Value::HasProperty { .. }
+            | Value::PropertyAccess { .. }
+            | Value::Variable { .. } => write!(f, "«thunk»"),
+        }
+    }
+}
+
+/// Whether an attribute name can be written without quotes.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    matches!(chars.next(), Some('a'..='z' | 'A'..='Z' | '_'))
+        && chars.all(|char| {
+            char.is_ascii_alphanumeric() || matches!(char, '_' | '\'' | '-')
+        })
+        && !matches!(
+            name,
+            "assert"
+                | "else"
+                | "if"
+                | "in"
+                | "inherit"
+                | "let"
+                | "or"
+                | "rec"
+                | "then"
+                | "with"
+        )
+}
+
+/// Quote a string like in Nix code.
+fn write_string(
+    f: &mut std::fmt::Formatter<'_>,
+    string: &str,
+) -> std::fmt::Result {
+    write!(f, "\"")?;
+    let mut chars = string.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            '$' if chars.peek() == Some(&'{') => write!(f, "\\$")?,
+            char => write!(f, "{char}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// The scope inherited bindings are evaluated in:
+/// the outer one, plus a thunk for the `from` of each `inherit (from) ...;`.
+///
+/// Like in Nix, `from` is evaluated in `scope_with_bindings`,
+/// so in a `let` or a `rec` set it can refer to the other bindings.
+fn scope_of_inherits(
+    inherit_from: Vec<(String, AST)>,
+    path: &Rc<String>,
+    scope: &Scope,
+    scope_with_bindings: &Scope,
+) -> Scope {
+    if inherit_from.is_empty() {
+        return scope.clone();
+    }
+
+    let scope_of_inherits = scope.derive(ScopeKind::Plain);
+
+    for (name, from) in inherit_from {
+        scope_of_inherits.bind(
+            name,
+            Rc::new(Value::thunk(from, path.clone(), scope_with_bindings)),
+        );
+    }
+
+    scope_of_inherits
+}
+
+/// Resolve a path literal against the directory of the file it appears in.
+fn absolute_path(file: &str, path: &str) -> String {
+    if path.starts_with('/') {
+        path.to_string()
+    } else if let Some(path) = path.strip_prefix('~') {
+        format!("{}{path}", std::env::var("HOME").unwrap_or_default())
+    } else {
+        let file = if file.starts_with('/') {
+            file.to_string()
+        } else {
+            let current_dir = std::env::current_dir().unwrap_or_default();
+
+            format!("{}/{file}", current_dir.display())
+        };
+
+        match file.rsplit_once('/') {
+            Some((directory, _)) => format!("{directory}/{path}"),
+            None => format!("/{path}"),
+        }
+    }
+}
+
+/// Remove `.`, `..` and repeated or trailing slashes from an absolute path,
+/// without touching the file system.
+pub(crate) fn canonicalize_path(path: &str) -> String {
+    let mut components = Vec::new();
+
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+
+    format!("/{}", components.join("/"))
+}
@@ -3,5 +3,8 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 
 pub mod cli;
+pub(crate) mod hash;
 pub(crate) mod interpreter;
 pub mod logger;
+pub(crate) mod regex;
+pub(crate) mod store;
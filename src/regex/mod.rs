@@ -0,0 +1,654 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::ops::Range;
+
+/// A POSIX extended regular expression,
+/// like the ones `builtins.match` and `builtins.split` take.
+///
+/// Nix uses the C++ standard library, which tries every alternative
+/// and keeps the longest match. Between matches of the same length
+/// the first one found wins, trying greedy repetitions first,
+/// and that one decides the groups.
+///
+/// Matching backtracks with a stack of its own rather than the native one,
+/// so long strings do not overflow it.
+pub(crate) struct Regex {
+    groups:      usize,
+    program:     Vec<Instruction>,
+    repetitions: usize,
+}
+
+/// The byte ranges of a match and of its groups,
+/// `None` for the groups that did not participate in it.
+pub(crate) type Captures = Vec<Option<Range<usize>>>;
+
+/// Like `Captures`, but in characters and while being built.
+type Positions = Vec<Option<(usize, usize)>>;
+
+enum Node {
+    Alternation(Vec<Node>),
+    Any,
+    Bracket { items: Vec<BracketItem>, negated: bool },
+    Char(char),
+    Concatenation(Vec<Node>),
+    End,
+    Group { index: usize, node: Box<Node> },
+    Repetition { max: Option<usize>, min: usize, node: Box<Node> },
+    Start,
+}
+
+enum BracketItem {
+    Char(char),
+    Class(fn(&char) -> bool),
+    Range(char, char),
+}
+
+/// A step of a compiled regular expression.
+enum Instruction {
+    Any,
+    Bracket {
+        items:   Vec<BracketItem>,
+        negated: bool,
+    },
+    Char(char),
+    End,
+    /// Continue with the next instruction,
+    /// and with the given one once that failed.
+    Fork(usize),
+    GroupEnd(usize),
+    GroupStart(usize),
+    Jump(usize),
+    Match,
+    /// Start another repetition with the body that follows, if allowed,
+    /// and continue at `exit` once that failed.
+    RepetitionHead {
+        repetition: usize,
+        max:        Option<usize>,
+        exit:       usize,
+    },
+    RepetitionStart(usize),
+    /// Where a repetition stops if it repeated enough.
+    RepetitionExit {
+        repetition: usize,
+        min:        usize,
+    },
+    /// Where the body of a repetition ends, it goes back to `head`.
+    RepetitionTail {
+        repetition: usize,
+        min:        usize,
+        head:       usize,
+    },
+    Start,
+}
+
+impl Regex {
+    pub(crate) fn new(pattern: &str) -> Result<Regex, String> {
+        let mut parser =
+            Parser { chars: pattern.chars().collect(), groups: 0, index: 0 };
+
+        let node = parser.alternation()?;
+
+        if parser.next().is_some() {
+            return Err("unmatched parenthesis".to_string());
+        }
+
+        let mut regex = Regex {
+            groups:      parser.groups,
+            program:     Vec::new(),
+            repetitions: 0,
+        };
+        regex.compile(node);
+        regex.program.push(Instruction::Match);
+
+        Ok(regex)
+    }
+
+    fn compile(&mut self, node: Node) {
+        match node {
+            Node::Alternation(branches) => {
+                let mut jumps = Vec::new();
+                let last = branches.len() - 1;
+
+                for (index, branch) in branches.into_iter().enumerate() {
+                    if index == last {
+                        self.compile(branch);
+                    } else {
+                        let fork = self.program.len();
+                        self.program.push(Instruction::Fork(0));
+                        self.compile(branch);
+                        jumps.push(self.program.len());
+                        self.program.push(Instruction::Jump(0));
+                        self.program[fork] =
+                            Instruction::Fork(self.program.len());
+                    }
+                }
+
+                for jump in jumps {
+                    self.program[jump] = Instruction::Jump(self.program.len());
+                }
+            }
+            Node::Any => self.program.push(Instruction::Any),
+            Node::Bracket { items, negated } => {
+                self.program.push(Instruction::Bracket { items, negated })
+            }
+            Node::Char(char) => self.program.push(Instruction::Char(char)),
+            Node::Concatenation(nodes) => {
+                for node in nodes {
+                    self.compile(node);
+                }
+            }
+            Node::End => self.program.push(Instruction::End),
+            Node::Group { index, node } => {
+                self.program.push(Instruction::GroupStart(index));
+                self.compile(*node);
+                self.program.push(Instruction::GroupEnd(index));
+            }
+            Node::Repetition { max, min, node } => {
+                let repetition = self.repetitions;
+                self.repetitions += 1;
+
+                self.program.push(Instruction::RepetitionStart(repetition));
+                let head = self.program.len();
+                self.program.push(Instruction::RepetitionHead {
+                    repetition,
+                    max,
+                    exit: 0,
+                });
+                self.compile(*node);
+                self.program.push(Instruction::RepetitionTail {
+                    repetition,
+                    min,
+                    head,
+                });
+                self.program[head] = Instruction::RepetitionHead {
+                    repetition,
+                    max,
+                    exit: self.program.len(),
+                };
+                self.program
+                    .push(Instruction::RepetitionExit { repetition, min });
+            }
+            Node::Start => self.program.push(Instruction::Start),
+        }
+    }
+
+    /// The groups of a match of the whole `string`, if any.
+    pub(crate) fn full_match(&self, string: &str) -> Option<Captures> {
+        let subject = Subject::new(string);
+
+        self.longest_at(&subject, 0, true, false)
+            .map(|positions| subject.captures(positions))
+    }
+
+    /// The successive non-overlapping matches in `string`.
+    ///
+    /// After an empty match, the next one must be non-empty
+    /// or start at the next character.
+    pub(crate) fn find_all(&self, string: &str) -> Vec<Captures> {
+        let subject = Subject::new(string);
+        let mut matches = Vec::new();
+
+        let mut found = self.search(&subject, 0);
+        while let Some(positions) = found {
+            let (start, end) = positions[0].unwrap();
+            matches.push(subject.captures(positions));
+
+            found = if start == end {
+                match self.longest_at(&subject, end, false, true) {
+                    Some(positions) => Some(positions),
+                    None if end < subject.chars.len() => {
+                        self.search(&subject, end + 1)
+                    }
+                    None => None,
+                }
+            } else {
+                self.search(&subject, end)
+            };
+        }
+
+        matches
+    }
+
+    /// The leftmost match starting at `from` or later.
+    fn search(&self, subject: &Subject, from: usize) -> Option<Positions> {
+        (from..=subject.chars.len())
+            .find_map(|start| self.longest_at(subject, start, false, false))
+    }
+
+    /// The longest match starting at `start`.
+    fn longest_at(
+        &self,
+        subject: &Subject,
+        start: usize,
+        whole: bool,
+        non_empty: bool,
+    ) -> Option<Positions> {
+        let length = subject.chars.len();
+        let mut longest: Option<(usize, Positions)> = None;
+
+        self.run(subject, start, &mut |end, positions| {
+            if (whole && end != length) || (non_empty && end == start) {
+                return false;
+            }
+
+            if longest.as_ref().is_none_or(|(longest, _)| end > *longest) {
+                longest = Some((end, positions.clone()));
+            }
+
+            // Nothing can be longer than the rest of the string
+            end == length
+        });
+
+        longest.map(|(end, mut positions)| {
+            positions[0] = Some((start, end));
+            positions
+        })
+    }
+
+    /// Match the program at `start`, calling `next` with where every
+    /// possible match ends, in the order they are found,
+    /// until it asks to stop by returning `true`.
+    fn run(
+        &self,
+        subject: &Subject,
+        start: usize,
+        next: &mut dyn FnMut(usize, &Positions) -> bool,
+    ) {
+        let mut registers = Registers {
+            captures:          vec![None; self.groups + 1],
+            counts:            vec![0; self.repetitions],
+            group_starts:      vec![0; self.groups + 1],
+            repetition_starts: vec![0; self.repetitions],
+            undo:              Vec::new(),
+        };
+        // Where to go back to once the current path fails,
+        // and how many register changes to undo then
+        let mut backtrack: Vec<(usize, usize, usize)> = Vec::new();
+        let mut pc = 0;
+        let mut position = start;
+
+        loop {
+            let char = subject.chars.get(position);
+
+            let proceed = match &self.program[pc] {
+                Instruction::Any => char.is_some(),
+                Instruction::Bracket { items, negated } => match char {
+                    Some(char) => {
+                        let matched = items.iter().any(|item| match item {
+                            BracketItem::Char(item) => item == char,
+                            BracketItem::Class(class) => class(char),
+                            BracketItem::Range(first, last) => {
+                                (first..=last).contains(&char)
+                            }
+                        });
+
+                        matched != *negated
+                    }
+                    None => false,
+                },
+                Instruction::Char(expected) => char == Some(expected),
+                Instruction::End => position == subject.chars.len(),
+                Instruction::Fork(target) => {
+                    backtrack.push((*target, position, registers.undo.len()));
+                    true
+                }
+                Instruction::GroupEnd(group) => {
+                    let start = registers.group_starts[*group];
+                    registers.capture(*group, Some((start, position)));
+                    true
+                }
+                Instruction::GroupStart(group) => {
+                    registers.set(Register::GroupStart(*group), position);
+                    true
+                }
+                Instruction::Jump(target) => {
+                    pc = *target;
+                    continue;
+                }
+                Instruction::Match => {
+                    if next(position, &registers.captures) {
+                        return;
+                    }
+                    false
+                }
+                Instruction::RepetitionHead { repetition, max, exit } => {
+                    // Greedy, one more repetition is tried before stopping
+                    if max.is_none_or(|max| registers.counts[*repetition] < max)
+                    {
+                        backtrack.push((*exit, position, registers.undo.len()));
+                        registers.set(
+                            Register::RepetitionStart(*repetition),
+                            position,
+                        );
+                        true
+                    } else {
+                        pc = *exit;
+                        continue;
+                    }
+                }
+                Instruction::RepetitionStart(repetition) => {
+                    registers.set(Register::Count(*repetition), 0);
+                    true
+                }
+                Instruction::RepetitionExit { repetition, min } => {
+                    registers.counts[*repetition] >= *min
+                }
+                Instruction::RepetitionTail { repetition, min, head } => {
+                    let count = registers.counts[*repetition];
+
+                    // Repeating what matches nothing would never end
+                    if position == registers.repetition_starts[*repetition]
+                        && count >= *min
+                    {
+                        false
+                    } else {
+                        registers.set(Register::Count(*repetition), count + 1);
+                        pc = *head;
+                        continue;
+                    }
+                }
+                Instruction::Start => position == 0,
+            };
+
+            if proceed {
+                if matches!(
+                    self.program[pc],
+                    Instruction::Any
+                        | Instruction::Bracket { .. }
+                        | Instruction::Char(_)
+                ) {
+                    position += 1;
+                }
+                pc += 1;
+            } else {
+                match backtrack.pop() {
+                    Some((target, target_position, undo)) => {
+                        registers.undo_until(undo);
+                        pc = target;
+                        position = target_position;
+                    }
+                    None => return,
+                }
+            }
+        }
+    }
+}
+
+struct Parser {
+    chars:  Vec<char>,
+    groups: usize,
+    index:  usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek();
+        self.index += 1;
+        char
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.concatenation()?];
+
+        while self.peek() == Some('|') {
+            self.next();
+            branches.push(self.concatenation()?);
+        }
+
+        if branches.len() == 1 {
+            Ok(branches.pop().unwrap())
+        } else {
+            Ok(Node::Alternation(branches))
+        }
+    }
+
+    fn concatenation(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+
+        while !matches!(self.peek(), None | Some('|') | Some(')')) {
+            nodes.push(self.repetition()?);
+        }
+
+        Ok(Node::Concatenation(nodes))
+    }
+
+    fn repetition(&mut self) -> Result<Node, String> {
+        let mut node = self.atom()?;
+
+        while let Some(quantifier @ ('*' | '+' | '?' | '{')) = self.peek() {
+            self.next();
+
+            let (min, max) = match quantifier {
+                '*' => (0, None),
+                '+' => (1, None),
+                '?' => (0, Some(1)),
+                _ => self.bound()?,
+            };
+
+            node = Node::Repetition { max, min, node: Box::new(node) };
+        }
+
+        Ok(node)
+    }
+
+    /// The rest of a bound like `{2}`, `{2,}` or `{2,3}`.
+    fn bound(&mut self) -> Result<(usize, Option<usize>), String> {
+        let min = self.number().ok_or("invalid repetition bound")?;
+        let max = if self.peek() == Some(',') {
+            self.next();
+            self.number()
+        } else {
+            Some(min)
+        };
+
+        match (self.next(), max) {
+            (Some('}'), Some(max)) if max < min => {
+                Err("invalid repetition bound".to_string())
+            }
+            (Some('}'), max) => Ok((min, max)),
+            _ => Err("unmatched brace".to_string()),
+        }
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let mut number = None;
+
+        while let Some(digit) = self.peek().and_then(|char| char.to_digit(10)) {
+            self.next();
+            number = Some(number.unwrap_or(0) * 10 + digit as usize);
+        }
+
+        number
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some('(') => {
+                self.groups += 1;
+                let index = self.groups;
+                let node = self.alternation()?;
+
+                match self.next() {
+                    Some(')') => {
+                        Ok(Node::Group { index, node: Box::new(node) })
+                    }
+                    _ => Err("unmatched parenthesis".to_string()),
+                }
+            }
+            Some('*' | '+' | '?' | '{') => {
+                Err("repetition without an operand".to_string())
+            }
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Start),
+            Some('$') => Ok(Node::End),
+            Some('[') => self.bracket(),
+            Some('\\') => match self.next() {
+                Some(char) => Ok(Node::Char(char)),
+                None => Err("trailing backslash".to_string()),
+            },
+            Some(char) => Ok(Node::Char(char)),
+            None => unreachable!(),
+        }
+    }
+
+    /// The rest of a bracket expression like `[a-z]` or `[^[:digit:]]`.
+    ///
+    /// A `]` right after the opening bracket is taken literally,
+    /// and so is a `-` at the start or at the end.
+    fn bracket(&mut self) -> Result<Node, String> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.next();
+        }
+
+        let mut items = Vec::new();
+        loop {
+            let char = match self.next() {
+                Some(']') if !items.is_empty() => break,
+                Some(char) => char,
+                None => return Err("unmatched bracket".to_string()),
+            };
+
+            if char == '[' && self.peek() == Some(':') {
+                self.next();
+                items.push(BracketItem::Class(self.class()?));
+            } else if self.peek() == Some('-')
+                && !matches!(self.chars.get(self.index + 1), None | Some(']'))
+            {
+                self.next();
+                let last = self.next().unwrap();
+
+                if last < char {
+                    return Err("invalid range".to_string());
+                }
+                items.push(BracketItem::Range(char, last));
+            } else {
+                items.push(BracketItem::Char(char));
+            }
+        }
+
+        Ok(Node::Bracket { items, negated })
+    }
+
+    /// The rest of a character class like `[:alpha:]`.
+    fn class(&mut self) -> Result<fn(&char) -> bool, String> {
+        let mut name = String::new();
+        loop {
+            match self.next() {
+                Some(':') if self.peek() == Some(']') => {
+                    self.next();
+                    break;
+                }
+                Some(char) => name.push(char),
+                None => return Err("unmatched bracket".to_string()),
+            }
+        }
+
+        match name.as_str() {
+            "alnum" => Ok(char::is_ascii_alphanumeric),
+            "alpha" => Ok(char::is_ascii_alphabetic),
+            "blank" => Ok(|char| matches!(char, ' ' | '\t')),
+            "cntrl" => Ok(char::is_ascii_control),
+            "digit" => Ok(char::is_ascii_digit),
+            "graph" => Ok(char::is_ascii_graphic),
+            "lower" => Ok(char::is_ascii_lowercase),
+            "print" => Ok(|char| char.is_ascii_graphic() || *char == ' '),
+            "punct" => Ok(char::is_ascii_punctuation),
+            "space" => Ok(|char| {
+                matches!(char, ' ' | '\t' | '\n' | '\r' | '\x0b' | '\x0c')
+            }),
+            "upper" => Ok(char::is_ascii_uppercase),
+            "xdigit" => Ok(char::is_ascii_hexdigit),
+            _ => Err(format!("unknown character class {name:?}")),
+        }
+    }
+}
+
+/// The string a regular expression is matched against.
+struct Subject {
+    chars:   Vec<char>,
+    /// Where each character starts, and where the string ends.
+    offsets: Vec<usize>,
+}
+
+impl Subject {
+    fn new(string: &str) -> Subject {
+        let (offsets, chars) = string.char_indices().unzip();
+        let mut subject = Subject { chars, offsets };
+        subject.offsets.push(string.len());
+        subject
+    }
+
+    fn captures(&self, positions: Positions) -> Captures {
+        positions
+            .into_iter()
+            .map(|position| {
+                position
+                    .map(|(start, end)| self.offsets[start]..self.offsets[end])
+            })
+            .collect()
+    }
+}
+
+/// A place where matching records a position or a count.
+#[derive(Clone, Copy)]
+enum Register {
+    Count(usize),
+    GroupStart(usize),
+    RepetitionStart(usize),
+}
+
+enum Undo {
+    Capture(usize, Option<(usize, usize)>),
+    Register(Register, usize),
+}
+
+/// What the current path of a match recorded,
+/// with the previous values to go back to when it fails.
+struct Registers {
+    captures:          Positions,
+    counts:            Vec<usize>,
+    group_starts:      Vec<usize>,
+    repetition_starts: Vec<usize>,
+    undo:              Vec<Undo>,
+}
+
+impl Registers {
+    fn capture(&mut self, group: usize, value: Option<(usize, usize)>) {
+        let previous = std::mem::replace(&mut self.captures[group], value);
+        self.undo.push(Undo::Capture(group, previous));
+    }
+
+    fn set(&mut self, register: Register, value: usize) {
+        let previous = std::mem::replace(self.register(register), value);
+        self.undo.push(Undo::Register(register, previous));
+    }
+
+    fn register(&mut self, register: Register) -> &mut usize {
+        match register {
+            Register::Count(index) => &mut self.counts[index],
+            Register::GroupStart(index) => &mut self.group_starts[index],
+            Register::RepetitionStart(index) => {
+                &mut self.repetition_starts[index]
+            }
+        }
+    }
+
+    /// Go back to when there were `length` changes.
+    fn undo_until(&mut self, length: usize) {
+        while self.undo.len() > length {
+            match self.undo.pop().unwrap() {
+                Undo::Capture(group, value) => self.captures[group] = value,
+                Undo::Register(register, value) => {
+                    *self.register(register) = value
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+pub(crate) mod nar;
+
+use std::path::Path;
+
+use crate::hash::base32;
+use crate::hash::sha256::Sha256;
+
+pub(crate) const STORE_DIR: &str = "/nix/store";
+
+/// Compute the store path that `path` gets when copied into the store,
+/// that is, hashing its Nix Archive (NAR) serialization.
+pub(crate) fn path_to_store_path(path: &str) -> Result<String, std::io::Error> {
+    let mut sha256 = Sha256::new();
+    nar::dump(Path::new(path), &mut sha256)?;
+
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    Ok(make_store_path("source", &sha256.finalize(), name))
+}
+
+pub(crate) fn make_store_path(
+    kind: &str,
+    hash: &[u8; 32],
+    name: &str,
+) -> String {
+    let hash: String = hash.iter().map(|byte| format!("{byte:02x}")).collect();
+
+    let mut sha256 = Sha256::new();
+    sha256
+        .update(format!("{kind}:sha256:{hash}:{STORE_DIR}:{name}").as_bytes());
+
+    let mut hash_compressed = [0; 20];
+    for (index, byte) in sha256.finalize().into_iter().enumerate() {
+        hash_compressed[index % 20] ^= byte;
+    }
+
+    format!("{STORE_DIR}/{}-{name}", base32::encode(&hash_compressed))
+}
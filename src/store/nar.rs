@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-only
+
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// Serialize `path` as a Nix Archive (NAR) into `sink`.
+pub(crate) fn dump(path: &Path, sink: &mut impl Write) -> std::io::Result<()> {
+    write_string(sink, b"nix-archive-1")?;
+    dump_node(path, sink)
+}
+
+fn dump_node(path: &Path, sink: &mut impl Write) -> std::io::Result<()> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    let file_type = metadata.file_type();
+
+    write_string(sink, b"(")?;
+    write_string(sink, b"type")?;
+
+    if file_type.is_symlink() {
+        let target = std::fs::read_link(path)?;
+
+        write_string(sink, b"symlink")?;
+        write_string(sink, b"target")?;
+        write_string(sink, target.to_string_lossy().as_bytes())?;
+    } else if file_type.is_dir() {
+        let mut entries = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+
+        write_string(sink, b"directory")?;
+        for entry in entries {
+            write_string(sink, b"entry")?;
+            write_string(sink, b"(")?;
+            write_string(sink, b"name")?;
+            write_string(sink, entry.to_string_lossy().as_bytes())?;
+            write_string(sink, b"node")?;
+            dump_node(&path.join(&entry), sink)?;
+            write_string(sink, b")")?;
+        }
+    } else {
+        let contents = std::fs::read(path)?;
+
+        write_string(sink, b"regular")?;
+        if metadata.permissions().mode() & 0o100 != 0 {
+            write_string(sink, b"executable")?;
+            write_string(sink, b"")?;
+        }
+        write_string(sink, b"contents")?;
+        write_string(sink, &contents)?;
+    }
+
+    write_string(sink, b")")
+}
+
+fn write_string(sink: &mut impl Write, string: &[u8]) -> std::io::Result<()> {
+    sink.write_all(&(string.len() as u64).to_le_bytes())?;
+    sink.write_all(string)?;
+    sink.write_all(&[0; 8][..(8 - string.len() % 8) % 8])
+}
@@ -0,0 +1,5 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[ 1 ] ++ [ ] ++ [ 2 3 ]
@@ -0,0 +1,12 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  check = name: value:
+    builtins.addErrorContext "while checking ${name}" (assert value > 0; value);
+in
+[
+  (check "a" 1)
+  (check "b" 0)
+]
@@ -0,0 +1,8 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  zero = 1 - 1;
+in
+  1 / zero
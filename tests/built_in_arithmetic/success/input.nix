@@ -0,0 +1,11 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  (7 - 10)
+  (6 * 7)
+  (7 / 2)
+  (0 - 7 / 2)
+  (1 + 2 * 3 - 4)
+]
@@ -0,0 +1,15 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  failing = assert false; 0;
+in
+[
+  (builtins.attrNames (builtins.mapAttrs (name: value: failing) { a = failing; }))
+  (builtins.attrNames (builtins.zipAttrsWith failing [ { a = failing; } ]))
+  (builtins.length (builtins.attrValues { a = failing; }))
+  (builtins.length (builtins.catAttrs "a" [ { a = failing; } ]))
+  (builtins.attrNames (builtins.listToAttrs [ { name = "a"; value = failing; } ]))
+  (({ a = failing; } // { b = 2; }).b)
+]
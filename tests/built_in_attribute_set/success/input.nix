@@ -0,0 +1,25 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  set = { b = 2; a = 1; c = 3; };
+in
+[
+  (builtins.attrNames set)
+  (builtins.attrValues set)
+  (builtins.mapAttrs (name: value: name + "=" + value) { a = "1"; b = "2"; })
+  (builtins.listToAttrs [
+    { name = "x"; value = 1; }
+    { name = "y"; value = 2; }
+    { name = "x"; value = 3; }
+  ])
+  (removeAttrs set [ "a" "z" ])
+  (builtins.intersectAttrs { a = null; c = null; d = null; } set)
+  (builtins.catAttrs "a" [ { a = 1; } { b = 2; } { a = 3; } ])
+  (builtins.zipAttrsWith (name: values: values) [ { a = 1; } { a = 2; b = 3; } ])
+  (builtins.getAttr "b" set)
+  (builtins.hasAttr "b" set)
+  (builtins.hasAttr "z" set)
+  ({ a = 1; b = 2; } // { b = 3; c = 4; })
+]
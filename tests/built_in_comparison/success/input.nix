@@ -0,0 +1,14 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  (1 < 2)
+  (2 <= 2)
+  (3 > 2)
+  (2 >= 3)
+  ("a" < "b")
+  ([ 1 2 ] < [ 1 3 ])
+  ([ 1 2 ] < [ 1 2 3 ])
+  ([ 2 ] >= [ 1 2 3 ])
+]
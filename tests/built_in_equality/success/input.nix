@@ -0,0 +1,15 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  (1 == 1)
+  (1 != 1)
+  ("a" == "a")
+  ([ 1 [ 2 ] ] == [ 1 [ 2 ] ])
+  ({ a = 1; b = { c = [ 3 ]; }; } == { b = { c = [ 3 ]; }; a = 1; })
+  ({ a = 1; } == { a = 1; b = 2; })
+  (1 == "1")
+  ((x: x) == (x: x))
+  ("${./input.nix}" == "${./input.nix}")
+]
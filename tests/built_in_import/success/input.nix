@@ -0,0 +1,13 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  increment = import ./increment.nix;
+in
+  [
+    (increment 1)
+    (import ./increment.nix 2)
+    (import ./directory).answer
+    (import ./directory/default.nix).answer
+  ]
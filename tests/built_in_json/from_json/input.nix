@@ -0,0 +1,12 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+builtins.fromJSON ''
+  {
+    "int": -12, "float": 1.5e3, "zero": 0.0, "negative": -9223372036854775809,
+    "string": "a\"b\\c\/\n\u00fc\ud83d\ude00",
+    "list": [ true, false, null, [], {} ],
+    "again": 1, "again": 2
+  }
+''
@@ -0,0 +1,5 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let value = { a = [ 1 2.5 "x" null ]; b.c = true; }; in builtins.fromJSON (builtins.toJSON value) == value
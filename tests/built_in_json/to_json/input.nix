@@ -0,0 +1,15 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+{
+  attributes = builtins.toJSON { b = [ 1 2.5 null ]; a = true; "c d" = "e"; };
+  escapes = builtins.toJSON "\"\\\n\r\t ü";
+  control = builtins.toJSON (builtins.fromJSON "\"\\u0001\\u001f\\u007f\"");
+  floats = builtins.toJSON [ 0.0 1.0 100.0 0.1 1.5e20 1.0e-5 0.001 (-2.5) 123456789012345.0 1234567890123456.0 ];
+  to_string = builtins.toJSON { __toString = self: "x"; outPath = "y"; };
+  out_path = builtins.toJSON [ { outPath = { a = 1; }; } ];
+  path = builtins.toJSON ./input.nix;
+  context = builtins.toJSON [ "${./input.nix}" ];
+  lazy = builtins.toJSON (builtins.genList (i: i * 2) 3);
+}
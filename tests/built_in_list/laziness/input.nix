@@ -0,0 +1,15 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  failing = assert false; 0;
+in
+[
+  (builtins.length (map (x: failing) [ 1 2 ]))
+  (builtins.length (builtins.genList (i: failing) 3))
+  (builtins.elemAt (map (x: x + 1) [ failing 2 ]) 1)
+  (builtins.length (map failing [ ]))
+  (builtins.any (x: x) [ true failing ])
+  (builtins.all (x: x) [ false failing ])
+]
@@ -0,0 +1,27 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  pairs = [
+    { key = 2; name = "a"; }
+    { key = 1; name = "b"; }
+    { key = 2; name = "c"; }
+    { key = 1; name = "d"; }
+  ];
+in
+[
+  (map (x: x * 2) [ 1 2 3 ])
+  (builtins.filter (x: x > 1) [ 1 2 3 ])
+  (builtins.foldl' (acc: x: acc + x) 0 [ 1 2 3 4 ])
+  (builtins.genList (i: i * i) 5)
+  (builtins.elemAt [ 1 2 3 ] 1)
+  (builtins.length [ 1 2 3 ])
+  (builtins.sort builtins.lessThan [ 3 1 2 ])
+  (map (pair: pair.name) (builtins.sort (a: b: a.key < b.key) pairs))
+  (builtins.concatMap (x: [ x x ]) [ 1 2 ])
+  (builtins.all (x: x > 0) [ 1 2 ])
+  (builtins.all (x: x > 1) [ 1 2 ])
+  (builtins.any (x: x > 1) [ 1 2 ])
+  (builtins.any (x: x > 2) [ 1 2 ])
+]
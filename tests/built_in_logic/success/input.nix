@@ -0,0 +1,17 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  loop = loop;
+in
+  [
+    (true && false)
+    (true && true)
+    (false && loop)
+    (false || true)
+    (true || loop)
+    (false -> loop)
+    (true -> false)
+    (1 < 2 && 2 < 3)
+  ]
@@ -0,0 +1,8 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  min = 0 - 9223372036854775807 - 1;
+in
+  -min
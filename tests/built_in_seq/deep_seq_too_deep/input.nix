@@ -0,0 +1,8 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  deep = builtins.foldl' (nested: _: [ nested ]) [ ] (builtins.genList (x: x) 20000);
+in
+  builtins.deepSeq deep null
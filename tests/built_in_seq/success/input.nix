@@ -0,0 +1,12 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  failing = assert false; 0;
+in
+[
+  (builtins.seq [ failing ] 1)
+  (builtins.seq { a = failing; } 2)
+  (builtins.deepSeq [ [ 1 ] { a = 2; } ] 3)
+]
@@ -0,0 +1,18 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  path = "${./input.nix}";
+in
+[
+  (builtins.substring 0 3 path)
+  (builtins.replaceStrings [ "x" ] [ path ] "axb")
+  (builtins.concatStringsSep path [ "a" "b" ])
+  (baseNameOf path)
+  (dirOf path)
+  (toString [ path ])
+  (builtins.match "(.).*" path)
+  (builtins.split "-" path)
+  (builtins.stringLength path)
+]
@@ -0,0 +1,17 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  (builtins.match "ab" "abc")
+  (builtins.match "a(b)(c)?" "ab")
+  (builtins.match "[[:alpha:]]+([0-9]+)" "foo123")
+  (builtins.match "(.*)-([0-9.]+)" "hello-2.10")
+  (builtins.match ".*\\.nix" "default.nix")
+  (builtins.match "x{2,3}" "xxxx")
+  (builtins.match "(a|ab)(c|bcd)(d*)" "abcd")
+  (builtins.split "(a)b" "abc")
+  (builtins.split "(a)|(c)" "abc")
+  (builtins.split "a*" "xaay")
+  (builtins.split "[[:space:]]+" " foo  bar ")
+]
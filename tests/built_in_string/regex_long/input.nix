@@ -0,0 +1,11 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  long = builtins.concatStringsSep "" (builtins.genList (_: "ab") 200000);
+in
+  {
+    match = builtins.match "(a|b)*" long;
+    split = builtins.length (builtins.split "(a|b)*c?" long);
+  }
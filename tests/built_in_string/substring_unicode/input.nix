@@ -0,0 +1,8 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  (builtins.substring 0 2 "ñandú")
+  (builtins.substring 2 5 "ñandú")
+]
@@ -0,0 +1,34 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  (builtins.stringLength "hello")
+  (builtins.substring 1 3 "hello")
+  (builtins.substring 3 (-1) "hello")
+  (builtins.substring 10 2 "hello")
+  (builtins.replaceStrings [ "o" "l" ] [ "0" "1" ] "hello world")
+  (builtins.replaceStrings [ "" ] [ "-" ] "abc")
+  (builtins.replaceStrings [ "aa" "a" ] [ "b" "c" ] "aaa")
+  (builtins.concatStringsSep ", " [ "a" "b" "c" ])
+  (builtins.concatStringsSep ", " [ ])
+  (toString 1)
+  (toString 1.5)
+  (toString true)
+  (toString false)
+  (toString null)
+  (toString [ 1 [ ] "a" [ 2 3 ] ])
+  (toString { __toString = self: "custom"; })
+  (toString { outPath = "out"; })
+  (baseNameOf "/a/b/c.nix")
+  (baseNameOf "/a/b/")
+  (baseNameOf "c")
+  (dirOf "/a/b/c.nix")
+  (dirOf "/a")
+  (dirOf "c")
+  (dirOf /a/b)
+  (builtins.hashString "md5" "hello world")
+  (builtins.hashString "sha1" "hello world")
+  (builtins.hashString "sha256" "hello world")
+  (builtins.hashString "sha512" "hello world")
+]
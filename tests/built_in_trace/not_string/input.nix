@@ -0,0 +1,17 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  x = 1;
+in
+  builtins.trace {
+    inherit x;
+    list = [ (x + 1) 2.5 null true ];
+    string = "a\"b\\c\n\${d}";
+    "with space" = { };
+    "if" = [ ];
+    function = y: y;
+    primop = builtins.add;
+    partial = builtins.add 1;
+  } x
@@ -0,0 +1,9 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  (builtins.trace "hello" 1)
+  (builtins.trace [ 1 { a = 2; } ] 2)
+  (builtins.traceVerbose (throw "not forced") 3)
+]
@@ -0,0 +1,16 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  failing = throw "failing";
+in
+[
+  (builtins.tryEval 1)
+  (builtins.tryEval (throw "message"))
+  (builtins.tryEval (assert false; 1))
+  (builtins.tryEval [ failing ]).success
+  (builtins.tryEval (map (x: x + failing) [ 1 ])).success
+  (builtins.tryEval (builtins.length [ failing ]))
+  (builtins.tryEval failing)
+]
@@ -0,0 +1,28 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  (map builtins.typeOf [
+    1
+    1.5
+    true
+    "a"
+    ./input.nix
+    null
+    [ ]
+    { }
+    (x: x)
+    map
+    (builtins.add 1)
+  ])
+  (builtins.isAttrs { })
+  (builtins.isBool false)
+  (builtins.isFloat 1)
+  (builtins.isFunction builtins.add)
+  (builtins.isInt 1)
+  (builtins.isList [ ])
+  (isNull null)
+  (builtins.isPath ./input.nix)
+  (builtins.isString "a")
+]
@@ -62,10 +62,7 @@ fn test_bin_toros() {
 fn find_files(path: &str) -> Vec<String> {
     walkdir::WalkDir::new(path)
         .into_iter()
-        .filter_map(|entry| match entry {
-            Ok(entry) => Some(entry),
-            Err(_) => None,
-        })
+        .filter_map(|entry| entry.ok())
         .filter(|entry| entry.file_type().is_file())
         .map(|entry| entry.path().to_str().unwrap().to_string())
         .collect()
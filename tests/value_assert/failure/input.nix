@@ -0,0 +1,10 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  x = 1;
+in
+  assert x == 1;
+  assert x == 2;
+  x
@@ -0,0 +1,23 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  name = "b";
+  set = rec {
+    a = 1;
+    ${name} = a;
+    "c-d" = 2;
+    "e${name}" = 3;
+    ${null} = 4;
+    f.${name} = 5;
+    f.g = 6;
+  };
+in
+  [
+    set
+    set."c-d"
+    set.${name}
+    set.f.${"g"}
+    set.missing or 7
+  ]
@@ -0,0 +1,11 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  name = "a";
+in
+  {
+    a = 1;
+    ${name} = 2;
+  }
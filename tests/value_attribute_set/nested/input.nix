@@ -0,0 +1,21 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  x = 0;
+  set = {
+    a.b = 1;
+    a.c.d = 2;
+    a.c.e = 3;
+    f = { g = 4; inherit x; };
+    f.h = 5;
+    i.j = 6;
+    i = { k = 7; };
+  };
+in
+  [
+    set
+    (let a.b = 1; a.c = 2; in a)
+    (rec { a.b = c; c = 3; })
+  ]
@@ -0,0 +1,9 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+{
+  a.b = 1;
+  a.c = 2;
+  a = { b = 3; };
+}
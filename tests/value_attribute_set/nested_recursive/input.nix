@@ -0,0 +1,14 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  x = { p = 3; };
+  b = "outer";
+in
+{
+  a = rec { b = 1; c = b; d = e; inherit (x) p; q = p; inherit x; };
+  a.e = 2;
+  f = { b = 10; g = b; };
+  f.h = 4;
+}
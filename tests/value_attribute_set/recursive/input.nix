@@ -0,0 +1,15 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  a = 10;
+in
+  rec {
+    a = 1;
+    b = a + 1;
+    c.d = b + e.f;
+    e = { f = a; };
+    g = a + h;
+    h = 100;
+  }
@@ -0,0 +1,10 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+{
+  a = 1;
+  b.c = 2;
+  d.e = 3;
+  f = a: a;
+}
@@ -0,0 +1,19 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  increment = builtins.add 1;
+in
+  [
+    (increment 2)
+    (builtins.sub 10 3)
+    (builtins.mul 6 7)
+    (builtins.div 7 2)
+    (builtins.lessThan 1 2)
+    builtins.null
+    (builtins.true && true)
+    (builtins ? import)
+    (builtins ? map)
+    (with builtins; add 1 2)
+  ]
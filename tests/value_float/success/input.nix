@@ -0,0 +1,23 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  1.5
+  1.0
+  0.1
+  (1.0 / 3)
+  123456.7
+  1234567.0
+  0.0001
+  0.00001
+  1.0e20
+  (0 - 2.5e-10)
+  (1 + 0.5)
+  (3 * 0.5)
+  (7 / 2.0)
+  (-1.5)
+  (1 == 1.0)
+  (1 < 1.5)
+  (2.0 - 1)
+]
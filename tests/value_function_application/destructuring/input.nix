@@ -0,0 +1,16 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  f = { a, b ? a + 1, ... }@args: [ a b args.c ];
+  g = { a ? b, b ? 2 }: a;
+  h = {}: 1;
+in
+  [
+    (f { a = 1; c = 3; })
+    (f { a = 1; b = 5; c = 3; })
+    (g { })
+    (h { })
+    ((args@{ a, ... }: args.b) { a = 4; b = 5; })
+  ]
@@ -0,0 +1,17 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  name = "b";
+  set = { a.b = 1; "c-d" = 2; };
+in
+  [
+    (set ? a)
+    (set ? a.b)
+    (set ? a.${name})
+    (set ? a.c)
+    (set ? "c-d")
+    (set ? a.b.c)
+    (1 ? a)
+  ]
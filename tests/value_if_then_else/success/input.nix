@@ -0,0 +1,13 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  loop = loop;
+in
+  [
+    (if true then 1 else loop)
+    (if 1 > 2 then loop else 2)
+    (if !false then -3 else 4)
+    (- (- 5))
+  ]
@@ -0,0 +1,11 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  a = 1;
+in
+  {
+    inherit "a";
+    a = 2;
+  }
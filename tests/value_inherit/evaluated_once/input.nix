@@ -0,0 +1,12 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  inherit (builtins.trace "eval" { x = 1; y = 2; }) x y;
+  set = { inherit (builtins.trace "nested" { a = 1; b = 2; }) a b; };
+  merged = { inherit (builtins.trace "merged" { c = 3; }) c; };
+  merged.d = 4;
+  r = rec { inherit (builtins.trace "rec" { e = 5; f = 6; }) e f; g = e + f; };
+in
+  [ (x + y) (set.a + set.b) merged r.g ]
@@ -0,0 +1,17 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  x = { a = 1; };
+  outer = { b = 2; };
+in
+  let
+    pkgs = { lib = { c = 3; }; };
+    inherit (pkgs) lib;
+    inherit (lib) c;
+    inherit (x) a;
+    set = rec { y = { d = 4; }; inherit (y) d; };
+    plain = { outer = { b = 5; }; inherit (outer) b; };
+  in
+    [ a c set.d plain.b ]
@@ -0,0 +1,21 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  a = 1;
+  set = { b = 2; c = 3; d = a; };
+  loop = loop;
+in
+  let
+    inherit a;
+    inherit (set) b c;
+  in
+    [
+      a
+      b
+      c
+      { inherit a; inherit (set) d; }
+      rec { a = 10; inherit (set) d; e = a; }
+      (let inherit (loop) x; in 4)
+    ]
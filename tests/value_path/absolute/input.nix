@@ -0,0 +1,10 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  /absolute/path
+  /absolute/./path/../other/path
+  (/absolute + "/path")
+  (/absolute + /path)
+]
@@ -0,0 +1,14 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  name = "file.nix";
+in
+  [
+    "${./directory}"
+    "${./directory/file.nix}"
+    "${./directory/${name}}"
+    "${./directory + "/file.nix"}"
+    ("string " + ../interpolation/directory/file.nix)
+  ]
@@ -0,0 +1,24 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  name = "world";
+in
+  [
+    ''
+      line 1
+        ${name} on line 2
+      line 3
+    ''
+    ''
+        ${name}
+      ends the indentation
+    ''
+    ''  no new line
+        after the start''
+    ''
+      ''${escaped} '''quoted''' ''\t
+    ''
+    ''''
+  ]
@@ -0,0 +1,18 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  name = "world";
+  derivation = { outPath = "/out"; };
+  object = {
+    __toString = self: "object with ${self.field}";
+    field = "a field";
+  };
+in
+  [
+    "hello ${name}!"
+    "${derivation}/bin"
+    "${object}"
+    "${"nested ${"${name}"}"}"
+  ]
@@ -0,0 +1,9 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+[
+  ""
+  "a \"quoted\" string\twith\nescapes \${not_interpolated}"
+  ("a" + "b" + "")
+]
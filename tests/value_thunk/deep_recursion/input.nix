@@ -0,0 +1,9 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  sum = n: if n == 0 then 0 else 1 + sum (n - 1);
+  count = n: acc: if n == 0 then acc else count (n - 1) (acc + 1);
+in
+  [ (sum 1500) (count 1500 0) ]
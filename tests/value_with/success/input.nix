@@ -0,0 +1,15 @@
+# SPDX-FileCopyrightText: 2022 Kevin Amado <kamadorueda@gmail.com>
+#
+# SPDX-License-Identifier: AGPL-3.0-only
+
+let
+  a = "lexical";
+  loop = loop;
+  set = { a = "with"; b = "outer"; c = "outer"; };
+in
+  [
+    (with set; a)
+    (with set; with { b = "inner"; }; [ b c ])
+    (with loop; a)
+    (with set; let c = "let"; in c)
+  ]